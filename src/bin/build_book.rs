@@ -0,0 +1,30 @@
+// Recommended usage: `cargo run --release --bin build_book -- openings/openings.pgn openings/openings.book 24`
+
+use std::env;
+
+use sisyphus32::{PgnReader, StatsBookBuilder};
+
+const DEFAULT_PGN_PATH: &str = "openings/openings.pgn";
+const DEFAULT_BOOK_PATH: &str = "openings/openings.book";
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let pgn_path = args.get(1).map_or(DEFAULT_PGN_PATH, String::as_str);
+    let book_path = args.get(2).map_or(DEFAULT_BOOK_PATH, String::as_str);
+    let mut builder = match args.get(3) {
+        Some(max_plies) => StatsBookBuilder::new(max_plies.parse().expect("Couldn't parse max plies")),
+        None => StatsBookBuilder::default(),
+    };
+
+    let mut num_games = 0;
+    for pgn_game_result in PgnReader::from_file(pgn_path).expect("Couldn't read PGN file") {
+        match pgn_game_result {
+            Ok(pgn_game) => num_games += builder.add_game(&pgn_game) as usize,
+            Err(error) => eprintln!("Skipped game: {error}"),
+        }
+    }
+
+    builder.write(book_path).expect("Couldn't write opening book");
+
+    println!("Added {num_games} games with {} entries to {book_path}", builder.num_entries());
+}
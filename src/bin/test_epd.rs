@@ -0,0 +1,102 @@
+// Recommended usage: `cargo run --release --bin test_epd -- epd/tactics.epd depth 8 10`
+// NOTE: The limit type is one of time (in milliseconds), depth or nodes. The process fails if
+// fewer positions than the optional minimum are solved, so it can be used as a regression gate.
+
+use std::{env, fs, process::exit, time::Instant};
+
+use sisyphus32::{BitMove, EpdRecord, Search, SearchLimits};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 4 || args.len() > 5 {
+        eprintln!("Usage: {} <epd file> <time | depth | nodes> <limit> [min solved]", args[0]);
+        exit(1);
+    }
+
+    let epd_path = &args[1];
+    let limit_type = args[2].as_str();
+    let limit: u64 = args[3].parse().expect("Couldn't parse limit");
+    let min_solved: usize = args.get(4).map_or(0, |min_solved| min_solved.parse().expect("Couldn't parse min solved"));
+
+    let limits = match limit_type {
+        "time" => SearchLimits::new(None, Some(limit as u128)),
+        "depth" => SearchLimits::new(Some(limit as usize), None),
+        "nodes" => SearchLimits { nodes: Some(limit), ..Default::default() },
+        _ => {
+            eprintln!("Illegal limit type: {limit_type}");
+            exit(1);
+        },
+    };
+
+    let epd = fs::read_to_string(epd_path).expect("Couldn't read EPD file");
+    let records: Vec<(usize, EpdRecord)> = epd
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(index, line)| match EpdRecord::try_from(line) {
+            Ok(record) => (index + 1, record),
+            Err(error) => {
+                eprintln!("Couldn't parse line {} of {epd_path}: {error}", index + 1);
+                exit(1);
+            },
+        })
+        .filter(|(_, record)| record.has_solution())
+        .collect();
+
+    println!("Running {} positions from {epd_path} with {limit_type} {limit}", records.len());
+
+    let start_time = Instant::now();
+    let mut solution_times = Vec::new();
+    let mut failures = Vec::new();
+
+    for (index, (line_number, record)) in records.iter().enumerate() {
+        let name = record.id_option.clone().unwrap_or_else(|| format!("line {line_number}"));
+        let mut search = Search::default();
+        #[cfg(feature = "opening_book")]
+        search.set_own_book(false);
+
+        let best_move = search.go_with_limits(&record.position, limits.clone());
+        let san = best_move.bit_move.to_san_string(&record.position);
+        print!("[{:>4}/{:<4}] {name:<16} ", index + 1, records.len());
+
+        if record.is_solved_by(best_move) {
+            let solution_time = search.get_best_move_found_time();
+            println!("solved   {san:<8} in {solution_time} ms");
+            solution_times.push(solution_time);
+        } else {
+            let expected_moves = |bit_moves: &[BitMove]| bit_moves
+                .iter()
+                .map(|bit_move| bit_move.to_san_string(&record.position))
+                .collect::<Vec<String>>()
+                .join(" ");
+            print!("failed   {san:<8} score {}", best_move.score);
+            if !record.best_moves.is_empty() {
+                print!(" bm {}", expected_moves(&record.best_moves));
+            }
+            if !record.avoid_moves.is_empty() {
+                print!(" am {}", expected_moves(&record.avoid_moves));
+            }
+            if let Some(mate) = record.mate_option {
+                print!(" dm {mate}");
+            }
+            println!();
+            failures.push(name);
+        }
+    }
+
+    let num_solved = solution_times.len();
+    println!();
+    println!("Solved {num_solved}/{} positions ({:.1}%)", records.len(), 100.0 * num_solved as f64 / records.len().max(1) as f64);
+    if num_solved > 0 {
+        println!("Average time to solution: {} ms", solution_times.iter().sum::<u128>() / num_solved as u128);
+    }
+    println!("Total time: {} ms", start_time.elapsed().as_millis());
+    if !failures.is_empty() {
+        println!("Failed: {}", failures.join(", "));
+    }
+
+    if num_solved < min_solved {
+        eprintln!("Solved fewer than {min_solved} positions");
+        exit(1);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{EvalMove, MoveFlag, Piece, Position, Score, Square};
+use crate::{EvalMove, MoveFlag, Position, Score, Square};
+
+#[cfg(feature = "bb")]
+use crate::Piece;
 use core::fmt;
 use std::{cmp::Ordering, fmt::Display, hash::Hash, mem};
 
@@ -165,6 +168,16 @@ impl BitMove {
         (self.source(), self.target(), self.flag_option())
     }
 
+    #[inline(always)]
+    pub(crate) fn to_bits(self) -> u32 {
+        self.0 as u32
+    }
+
+    #[inline(always)]
+    pub(crate) fn from_bits(bits: u32) -> BitMove {
+        BitMove(bits as _)
+    }
+
     #[inline(always)]
     pub fn is_capture(self, position: &Position) -> bool {
         position.get_piece_option(self.target()).is_some()
@@ -175,16 +188,6 @@ impl BitMove {
         self.is_capture(position) || self.flag_option().is_some_and(|f| f.is_promotion())
     }
 
-    #[inline(always)]
-    pub(crate) fn is_pp_capture_or_castle(self, position: &Position) -> bool {
-        let source_piece = position.get_piece_option(self.source());
-        let target_piece = position.get_piece_option(self.target());
-        source_piece == Some(Piece::WP) ||
-        source_piece == Some(Piece::BP) ||
-        target_piece.is_some() ||
-        self.flag_option().is_some_and(|f| f.is_castle())
-    }
-
     pub fn to_uci_string(self) -> String {
         format!(
             "{}{}{}",
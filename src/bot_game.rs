@@ -1,6 +1,9 @@
 use std::{collections::HashMap};
 
-use crate::{BitMove, BotGameError, Color, HistoryHeuristic, KillerMoves, Legal, MoveGeneration, MoveList, Piece, Position, ScoringMove, Search, Square, TranspositionTable, Uci};
+use crate::{BitMove, BotGameError, Color, EvalPosition, FenString, GameResult, GameTermination, Legal, MoveGeneration, MoveList, PgnGame, Piece, Position, Score, ScoringMove, Search, Square, Uci, ZobristKey};
+
+const BOT_NAME: &str = "Sisyphus32";
+const PLAYER_NAME: &str = "Player";
 
 pub struct BotGame {
     thinking_time: u128,
@@ -8,7 +11,11 @@ pub struct BotGame {
     position: Position,
     search: Search,
     move_history: Vec<BitMove>,
+    position_history: Vec<Position>,
+    redo_moves: Vec<BitMove>,
+    zobrist_key_history: Vec<ZobristKey>,
     legal_moves: MoveList<BitMove>,
+    agreed_result_option: Option<GameResult>,
 }
 
 impl Default for BotGame {
@@ -19,17 +26,40 @@ impl Default for BotGame {
 
 impl BotGame {
     pub fn new(player_side: Color, thinking_time: u128) -> Self {
-        KillerMoves::reset();
-        HistoryHeuristic::reset();
-        TranspositionTable::reset();
-        
+        Self::from_position(player_side, thinking_time, Position::starting_position())
+    }
+
+    pub fn from_fen(player_side: Color, thinking_time: u128, fen: FenString) -> Result<Self, BotGameError> {
+        Ok(Self::from_position(player_side, thinking_time, fen.parse()?))
+    }
+
+    pub fn from_moves(player_side: Color, thinking_time: u128, uci_moves: &[&str]) -> Result<Self, BotGameError> {
+        Self::from_fen_and_moves(player_side, thinking_time, FenString::startpos(), uci_moves)
+    }
+
+    // NOTE: The moves are part of the game history, so they can be taken back and count towards
+    // repetitions. Positions from before the fen are unknown and can't.
+    pub fn from_fen_and_moves(player_side: Color, thinking_time: u128, fen: FenString, uci_moves: &[&str]) -> Result<Self, BotGameError> {
+        let mut bot_game = Self::from_fen(player_side, thinking_time, fen)?;
+        for uci_move in uci_moves {
+            let bit_move = Uci::parse_move_string(&bot_game.legal_moves, uci_move)?;
+            bot_game.apply_move(bit_move);
+        }
+        Ok(bot_game)
+    }
+
+    fn from_position(player_side: Color, thinking_time: u128, position: Position) -> Self {
         Self {
             thinking_time,
             player_side,
-            position: Position::starting_position(),
+            legal_moves: MoveGeneration::generate_moves::<BitMove, Legal>(&position),
+            position,
             search: Default::default(),
             move_history: Default::default(),
-            legal_moves: MoveGeneration::generate_moves::<BitMove, Legal>(&Position::starting_position())
+            position_history: Default::default(),
+            redo_moves: Default::default(),
+            zobrist_key_history: Default::default(),
+            agreed_result_option: None,
         }
     }
 
@@ -59,14 +89,30 @@ impl BotGame {
         Ok(())
     }
 
+    // NOTE: Skill levels range from 0 to 20, where 20 is full strength
+    pub fn set_skill_level(&mut self, skill_level: u8) -> Result<(), BotGameError> {
+        self.verify_player_to_move()?;
+        self.search.set_skill_level(skill_level);
+        Ok(())
+    }
+
+    pub fn set_elo(&mut self, elo: u16) -> Result<(), BotGameError> {
+        self.verify_player_to_move()?;
+        self.search.set_elo(elo);
+        Ok(())
+    }
+
     pub fn bot_play_move(&mut self) -> Result<ScoringMove, BotGameError> {
+        self.verify_game_not_over()?;
         self.verify_bot_to_move()?;
+        self.search.zobrist_key_history = self.zobrist_key_history.clone();
         let best_move = self.search.go(&self.position, None, Some(self.thinking_time));
         self.make_move(best_move.bit_move);
         Ok(best_move)
     }
 
     pub fn player_play_bit_move(&mut self, bit_move: BitMove) -> Result<(), BotGameError> {
+        self.verify_game_not_over()?;
         self.verify_player_to_move()?;
         if self.get_legal_moves().contains(&bit_move) {
             self.make_move(bit_move);
@@ -77,36 +123,171 @@ impl BotGame {
     }
 
     pub fn player_play_uci_move(&mut self, uci_move: &str) -> Result<(), BotGameError> {
+        self.verify_game_not_over()?;
         self.verify_player_to_move()?;
         let bit_move = Uci::parse_move_string(&self.legal_moves, uci_move).map_err(|_| BotGameError::IllegalUciMoveError)?;
         self.make_move(bit_move);
         Ok(())
     }
 
+    pub fn player_play_san_move(&mut self, san_move: &str) -> Result<(), BotGameError> {
+        self.verify_game_not_over()?;
+        self.verify_player_to_move()?;
+        let bit_move = self.position.parse_san(san_move)?;
+        self.make_move(bit_move);
+        Ok(())
+    }
+
+    // NOTE: Playing a new move discards the moves that could have been redone
     fn make_move(&mut self, bit_move: BitMove) {
+        self.redo_moves.clear();
+        self.apply_move(bit_move);
+    }
+
+    fn apply_move(&mut self, bit_move: BitMove) {
+        self.position_history.push(self.position.clone());
+        self.zobrist_key_history.push(self.position.zobrist_key);
         self.position.make_move(bit_move);
         self.move_history.push(bit_move);
         self.legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&self.position);
     }
 
+    // NOTE: A resignation or draw agreement no longer applies once the game is taken back
+    pub fn undo_move(&mut self) -> Result<BitMove, BotGameError> {
+        let bit_move = self.move_history.pop().ok_or(BotGameError::NoMoveToUndoError)?;
+        self.position = self.position_history.pop().expect("Position history should match move history");
+        self.zobrist_key_history.pop();
+        self.redo_moves.push(bit_move);
+        self.legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&self.position);
+        self.agreed_result_option = None;
+        Ok(bit_move)
+    }
+
+    pub fn redo_move(&mut self) -> Result<BitMove, BotGameError> {
+        let bit_move = self.redo_moves.pop().ok_or(BotGameError::NoMoveToRedoError)?;
+        self.apply_move(bit_move);
+        Ok(bit_move)
+    }
+
+    // Undoes moves until only the first move_count moves of the game remain
+    pub fn undo_to_move(&mut self, move_count: usize) -> Result<(), BotGameError> {
+        if move_count > self.move_history.len() {
+            return Err(BotGameError::NoMoveToUndoError);
+        }
+
+        while self.move_history.len() > move_count {
+            self.undo_move()?;
+        }
+
+        Ok(())
+    }
+
+    // Takes back the last move of the player, along with the reply of the bot if it has
+    // already been played, so it's the player's turn again
+    pub fn player_takeback(&mut self) -> Result<(), BotGameError> {
+        let move_count = match self.player_to_move() {
+            true => 2,
+            false => 1,
+        };
+
+        if self.move_history.len() < move_count {
+            return Err(BotGameError::NoMoveToUndoError);
+        }
+
+        for _ in 0..move_count {
+            self.undo_move()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.move_history.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_moves.is_empty()
+    }
+
+    pub fn player_resign(&mut self) -> Result<(), BotGameError> {
+        self.verify_game_not_over()?;
+        self.agreed_result_option = Some(GameResult::Win(self.bot_side(), GameTermination::Resignation));
+        Ok(())
+    }
+
+    // NOTE: The bot accepts a draw offer unless it evaluates its own position as better
+    pub fn player_offer_draw(&mut self) -> Result<bool, BotGameError> {
+        self.verify_game_not_over()?;
+        self.verify_player_to_move()?;
+        let bot_score = -EvalPosition::eval(&self.position);
+        if bot_score <= Score::ZERO {
+            self.agreed_result_option = Some(GameResult::Draw(GameTermination::DrawAgreement));
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub fn status(&self) -> GameResult {
+        if let Some(agreed_result) = self.agreed_result_option {
+            agreed_result
+        } else if self.is_checkmate() {
+            GameResult::Win(self.to_move().opposite(), GameTermination::Checkmate)
+        } else if self.is_stalemate() {
+            GameResult::Draw(GameTermination::Stalemate)
+        } else if self.is_insufficient_material() {
+            GameResult::Draw(GameTermination::InsufficientMaterial)
+        } else if self.is_fifty_move_draw() {
+            GameResult::Draw(GameTermination::FiftyMoveRule)
+        } else if self.is_threefold_repetition() {
+            GameResult::Draw(GameTermination::ThreefoldRepetition)
+        } else {
+            GameResult::Ongoing
+        }
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.status().is_over()
+    }
+
     pub fn is_checkmate(&self) -> bool {
-        self.get_legal_moves().is_empty()
+        self.get_legal_moves().is_empty() && self.in_check()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        self.get_legal_moves().is_empty() && !self.in_check()
+    }
+
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.position.is_fifty_move_draw()
+    }
+
+    pub fn is_threefold_repetition(&self) -> bool {
+        self.position.is_threefold_repetition(&self.zobrist_key_history)
+    }
+
+    pub fn is_insufficient_material(&self) -> bool {
+        self.position.is_insufficient_material()
+    }
+
+    pub fn is_draw(&self) -> bool {
+        matches!(self.status(), GameResult::Draw(_))
     }
 
     pub fn bot_won(&self) -> bool {
-        self.is_checkmate() && self.player_to_move()
+        self.status().winner() == Some(self.bot_side())
     }
 
     pub fn player_won(&self) -> bool {
-        self.is_checkmate() && self.bot_to_move()
+        self.status().winner() == Some(self.player_side())
     }
 
     pub fn white_won(&self) -> bool {
-        self.is_checkmate() && self.to_move() == Color::Black
+        self.status().winner() == Some(Color::White)
     }
 
     pub fn black_won(&self) -> bool {
-        self.is_checkmate() && self.to_move() == Color::White
+        self.status().winner() == Some(Color::Black)
     }
 
     pub fn player_legal_moves(&self) -> Result<&MoveList<BitMove>, BotGameError>  {
@@ -126,6 +307,14 @@ impl BotGame {
         }
     }
 
+    fn verify_game_not_over(&self) -> Result<(), BotGameError> {
+        if self.is_game_over() {
+            Err(BotGameError::GameOverError)
+        } else {
+            Ok(())
+        }
+    }
+
     fn verify_player_to_move(&self) -> Result<(), BotGameError> {
         self.verify_side_to_move(self.player_side())
     }
@@ -157,6 +346,28 @@ impl BotGame {
         &self.move_history
     }
 
+    pub fn get_san_move_history(&self) -> Vec<String> {
+        self.position_history
+            .iter()
+            .zip(&self.move_history)
+            .map(|(position, bit_move)| bit_move.to_san_string(position))
+            .collect()
+    }
+
+    // NOTE: The game starts from the first position of its history, so games created from a fen
+    // are exported with setup tags
+    pub fn to_pgn_game(&self) -> PgnGame {
+        let start_position = self.position_history.first().unwrap_or(&self.position).clone();
+        let mut pgn_game = PgnGame::new(start_position, self.move_history.clone(), &self.status().to_string());
+        let (white, black) = match self.player_side {
+            Color::White => (PLAYER_NAME, BOT_NAME),
+            Color::Black => (BOT_NAME, PLAYER_NAME),
+        };
+        pgn_game.set_tag("White", white);
+        pgn_game.set_tag("Black", black);
+        pgn_game
+    }
+
     pub fn get_last_move(&self) -> Option<BitMove> {
         self.get_move_history().last().copied()
     }
@@ -179,6 +390,8 @@ impl BotGame {
 
 #[cfg(test)]
 mod tests {
+    use crate::FenString;
+
     use super::*;
 
     #[test]
@@ -195,10 +408,204 @@ mod tests {
         assert!(bot_game.player_to_move());
     }
 
+    #[test]
+    fn bot_game_is_drawn_by_fifty_move_rule() {
+        assert!(!BotGame::new(Color::White, 1000).is_draw());
+        let bot_game = BotGame::from_fen(Color::White, 1000, FenString::from("8/8/4k3/8/8/3K4/8/7R w - - 100 80")).unwrap();
+        assert!(bot_game.is_fifty_move_draw());
+        assert!(bot_game.is_draw());
+    }
+
+    #[test]
+    fn bot_game_distinguishes_checkmate_from_stalemate() {
+        let bot_game = BotGame::from_fen(Color::White, 1000, FenString::from("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")).unwrap();
+        assert!(bot_game.is_stalemate());
+        assert!(!bot_game.is_checkmate());
+        assert!(bot_game.is_draw());
+        assert!(!bot_game.white_won());
+
+        let bot_game = BotGame::from_fen(Color::White, 1000, FenString::from("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1")).unwrap();
+        assert!(bot_game.is_checkmate());
+        assert!(!bot_game.is_draw());
+        assert!(bot_game.white_won());
+    }
+
+    #[test]
+    fn bot_game_is_drawn_by_threefold_repetition() {
+        let mut bot_game = BotGame::new(Color::White, 1000);
+        for _ in 0..2 {
+            assert!(!bot_game.is_threefold_repetition());
+            for uci_move in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                let bit_move = Uci::parse_move_string(bot_game.get_legal_moves(), uci_move).unwrap();
+                bot_game.make_move(bit_move);
+            }
+        }
+        assert!(bot_game.is_threefold_repetition());
+        assert!(bot_game.is_draw());
+    }
+
+    #[test]
+    fn bot_game_status_reports_result_and_termination() {
+        let mut bot_game = BotGame::new(Color::White, 1000);
+        assert_eq!(bot_game.status(), GameResult::Ongoing);
+        for uci_move in ["f2f3", "e7e5", "g2g4"] {
+            let bit_move = Uci::parse_move_string(bot_game.get_legal_moves(), uci_move).unwrap();
+            bot_game.make_move(bit_move);
+        }
+        let bit_move = Uci::parse_move_string(bot_game.get_legal_moves(), "d8h4").unwrap();
+        bot_game.make_move(bit_move);
+        assert_eq!(bot_game.status(), GameResult::Win(Color::Black, GameTermination::Checkmate));
+        assert_eq!(bot_game.status().to_string(), "0-1");
+        assert!(bot_game.bot_won() && bot_game.black_won());
+
+        let bot_game = BotGame::from_fen(Color::White, 1000, FenString::from("8/8/4k3/8/8/3K4/8/5B2 w - - 0 1")).unwrap();
+        assert_eq!(bot_game.status(), GameResult::Draw(GameTermination::InsufficientMaterial));
+    }
+
+    #[test]
+    fn bot_game_refuses_moves_after_game_over() {
+        let mut bot_game = BotGame::new(Color::White, 1000);
+        bot_game.player_resign().unwrap();
+        assert_eq!(bot_game.status(), GameResult::Win(Color::Black, GameTermination::Resignation));
+        assert!(matches!(bot_game.player_play_uci_move("e2e4"), Err(BotGameError::GameOverError)));
+        assert!(matches!(bot_game.player_resign(), Err(BotGameError::GameOverError)));
+
+        let mut bot_game = BotGame::from_fen(Color::Black, 1000, FenString::from("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")).unwrap();
+        assert!(matches!(bot_game.player_play_uci_move("h8g8"), Err(BotGameError::GameOverError)));
+    }
+
+    #[test]
+    fn bot_game_draw_offer_depends_on_bot_evaluation() {
+        let mut bot_game = BotGame::from_fen(Color::White, 1000, FenString::from("4k3/8/8/8/8/8/8/Q3K3 w - - 0 1")).unwrap();
+        assert!(bot_game.player_offer_draw().unwrap());
+        assert_eq!(bot_game.status(), GameResult::Draw(GameTermination::DrawAgreement));
+
+        let mut bot_game = BotGame::from_fen(Color::White, 1000, FenString::from("q3k3/8/8/8/8/8/8/4K3 w - - 0 1")).unwrap();
+        assert!(!bot_game.player_offer_draw().unwrap());
+        assert_eq!(bot_game.status(), GameResult::Ongoing);
+    }
+
+    fn play_uci_moves(bot_game: &mut BotGame, uci_moves: &[&str]) {
+        for uci_move in uci_moves {
+            let bit_move = Uci::parse_move_string(bot_game.get_legal_moves(), uci_move).unwrap();
+            bot_game.make_move(bit_move);
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_game() {
+        let mut bot_game = BotGame::new(Color::White, 1000);
+        assert!(matches!(bot_game.undo_move(), Err(BotGameError::NoMoveToUndoError)));
+        play_uci_moves(&mut bot_game, &["e2e4", "e7e5", "g1f3"]);
+        let fen = FenString::from(bot_game.get_position()).to_string();
+        let legal_moves = bot_game.get_legal_moves().len();
+
+        assert_eq!(bot_game.undo_move().unwrap().to_uci_string(), "g1f3");
+        assert_eq!(bot_game.undo_move().unwrap().to_uci_string(), "e7e5");
+        assert_eq!(bot_game.get_move_history().len(), 1);
+        assert_eq!(bot_game.get_legal_moves().len(), 20);
+        assert!(bot_game.can_redo());
+
+        bot_game.redo_move().unwrap();
+        bot_game.redo_move().unwrap();
+        assert!(matches!(bot_game.redo_move(), Err(BotGameError::NoMoveToRedoError)));
+        assert_eq!(FenString::from(bot_game.get_position()).to_string(), fen);
+        assert_eq!(bot_game.get_legal_moves().len(), legal_moves);
+
+        bot_game.undo_move().unwrap();
+        play_uci_moves(&mut bot_game, &["d2d4"]);
+        assert!(!bot_game.can_redo());
+    }
+
+    #[test]
+    fn undo_to_move_and_takeback_keep_game_state_consistent() {
+        let mut bot_game = BotGame::new(Color::White, 1000);
+        play_uci_moves(&mut bot_game, &["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"]);
+        assert_eq!(bot_game.status(), GameResult::Draw(GameTermination::ThreefoldRepetition));
+
+        bot_game.player_takeback().unwrap();
+        assert_eq!(bot_game.get_move_history().len(), 6);
+        assert!(bot_game.player_to_move());
+        assert_eq!(bot_game.status(), GameResult::Ongoing);
+
+        assert!(matches!(bot_game.undo_to_move(7), Err(BotGameError::NoMoveToUndoError)));
+        bot_game.undo_to_move(1).unwrap();
+        assert_eq!(bot_game.get_move_history().len(), 1);
+        assert_eq!(bot_game.zobrist_key_history.len(), 1);
+        assert!(bot_game.bot_to_move());
+
+        bot_game.player_takeback().unwrap();
+        assert!(bot_game.get_move_history().is_empty());
+        assert_eq!(bot_game.get_position().zobrist_key, Position::starting_position().zobrist_key);
+
+        bot_game.player_resign().unwrap();
+        play_uci_moves(&mut bot_game, &["e2e4"]);
+        bot_game.undo_move().unwrap();
+        assert!(!bot_game.is_game_over());
+    }
+
+    #[test]
+    fn bot_game_can_start_from_fen_and_moves() {
+        let bot_game = BotGame::from_fen(Color::Black, 1000, FenString::kiwipete()).unwrap();
+        assert_eq!(bot_game.get_legal_moves().len(), 48);
+        assert!(bot_game.bot_to_move());
+
+        let bot_game = BotGame::from_fen_and_moves(Color::White, 1000, FenString::kiwipete(), &["e1g1", "a6e2"]).unwrap();
+        assert_eq!(bot_game.get_move_history().len(), 2);
+        assert!(bot_game.player_to_move());
+
+        let mut bot_game = BotGame::from_moves(Color::White, 1000, &["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1"]).unwrap();
+        assert!(!bot_game.is_threefold_repetition());
+        play_uci_moves(&mut bot_game, &["f6g8"]);
+        assert!(bot_game.is_threefold_repetition());
+        assert!(matches!(bot_game.player_play_uci_move("e2e4"), Err(BotGameError::GameOverError)));
+    }
+
+    #[test]
+    fn bot_game_constructors_reject_invalid_input() {
+        assert!(matches!(BotGame::from_fen(Color::White, 1000, FenString::from("8/8/8 x - - 0 1")), Err(BotGameError::FenParseError(_))));
+        assert!(matches!(BotGame::from_moves(Color::White, 1000, &["e2e4", "e2e4"]), Err(BotGameError::MoveStringParseError(_))));
+        assert!(matches!(BotGame::from_moves(Color::White, 1000, &["e2e9"]), Err(BotGameError::MoveStringParseError(_))));
+    }
+
+    #[test]
+    fn limited_bot_plays_legal_moves() {
+        let mut bot_game = BotGame::new(Color::White, 1000);
+        bot_game.set_skill_level(0).unwrap();
+        play_uci_moves(&mut bot_game, &["e2e4"]);
+        let legal_moves: Vec<BitMove> = bot_game.get_legal_moves().iter().copied().collect();
+        let best_move = bot_game.bot_play_move().unwrap();
+        assert!(legal_moves.contains(&best_move.bit_move));
+        assert!(matches!(bot_game.set_elo(0), Ok(())));
+    }
+
+    #[test]
+    fn bot_game_plays_and_shows_san_moves() {
+        let mut bot_game = BotGame::new(Color::White, 1000);
+        bot_game.player_play_san_move("e4").unwrap();
+        play_uci_moves(&mut bot_game, &["e7e5", "d1h5", "b8c6", "f1c4", "g8f6"]);
+        assert!(matches!(bot_game.player_play_san_move("Qxg7"), Err(BotGameError::SanParseError(_))));
+        bot_game.player_play_san_move("Qxf7#").unwrap();
+        assert_eq!(bot_game.get_san_move_history(), ["e4", "e5", "Qh5", "Nc6", "Bc4", "Nf6", "Qxf7#"]);
+        assert!(bot_game.is_checkmate());
+    }
+
+    #[test]
+    fn bot_game_is_exported_as_pgn() {
+        let mut bot_game = BotGame::from_fen(Color::White, 1000, FenString::from("7k/8/6K1/8/8/8/8/Q7 w - - 0 40")).unwrap();
+        play_uci_moves(&mut bot_game, &["a1b1", "h8g8"]);
+        bot_game.player_play_san_move("Qb8#").unwrap();
+
+        let pgn = bot_game.to_pgn_game().to_string();
+        assert!(pgn.contains("[White \"Player\"]\n[Black \"Sisyphus32\"]\n[Result \"1-0\"]\n"));
+        assert!(pgn.contains("[FEN \"7k/8/6K1/8/8/8/8/Q7 w - - 0 40\"]"));
+        assert!(pgn.ends_with("\n\n40. Qb1 Kg8 41. Qb8# 1-0\n"));
+    }
+
     #[test]
     fn initial_bot_game_has_moves() {
         let bot_game = BotGame::new(Color::White, 1000);
-        assert!(bot_game.get_legal_moves().len() > 0);
+        assert!(!bot_game.get_legal_moves().is_empty());
     }
 
     #[cfg(feature = "bb_array")]
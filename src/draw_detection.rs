@@ -0,0 +1,127 @@
+use crate::{Bitboard, Piece, Position, ZobristKey};
+
+// NOTE: A position can only repeat after both sides have moved twice
+const MIN_REPETITION_DISTANCE: usize = 4;
+
+impl Position {
+    // NOTE: Only the positions where neither side can force checkmate are covered, which are
+    // K vs K, KB vs K, KN vs K and any number of bishops that are all on the same colored squares
+    pub fn is_insufficient_material(&self) -> bool {
+        let pawns_rooks_and_queens =
+            self.bitboards[Piece::WP] | self.bitboards[Piece::BP] |
+            self.bitboards[Piece::WR] | self.bitboards[Piece::BR] |
+            self.bitboards[Piece::WQ] | self.bitboards[Piece::BQ];
+
+        if pawns_rooks_and_queens.is_not_empty() {
+            return false;
+        }
+
+        let knights = self.bitboards[Piece::WN] | self.bitboards[Piece::BN];
+        let bishops = self.bitboards[Piece::WB] | self.bitboards[Piece::BB];
+
+        (knights | bishops).count_bits() <= 1 || knights.is_empty() && (
+            (bishops & Bitboard::WHITE_SQUARES).is_empty() ||
+            (bishops & Bitboard::BLACK_SQUARES).is_empty()
+        )
+    }
+
+    // Repetitions of positions inside the search are treated as draws right away, since the same
+    // moves can be repeated to reach a threefold repetition. Positions from before the root still
+    // have to occur three times.
+    pub fn is_repetition(&self, key_history: &[ZobristKey]) -> bool {
+        let mut repetitions = 0;
+        for distance in self.repetition_distances(key_history) {
+            if distance <= self.ply as usize {
+                return true;
+            }
+
+            repetitions += 1;
+            if repetitions >= 2 {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn is_threefold_repetition(&self, key_history: &[ZobristKey]) -> bool {
+        self.repetition_distances(key_history).nth(1).is_some()
+    }
+
+    // NOTE: The key history holds the keys of the positions before this one in the order they
+    // were played. Positions from before the last irreversible move can't occur again, so only
+    // the keys within the halfmove clock with the same side to move are scanned.
+    fn repetition_distances<'a>(&'a self, key_history: &'a [ZobristKey]) -> impl Iterator<Item = usize> + 'a {
+        let max_distance = key_history.len().min(self.halfmove_clock as usize);
+        (MIN_REPETITION_DISTANCE..=max_distance)
+            .step_by(2)
+            .filter(move |&distance| key_history[key_history.len() - distance] == self.zobrist_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitMove, FenString, Legal, MoveGeneration, Uci};
+
+    use super::*;
+
+    fn play_moves(position: &mut Position, key_history: &mut Vec<ZobristKey>, move_strings: &[&str]) {
+        for move_string in move_strings {
+            let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(position);
+            let bit_move = Uci::parse_move_string(&legal_moves, move_string).unwrap();
+            key_history.push(position.zobrist_key);
+            position.make_move(bit_move);
+        }
+    }
+
+    #[test]
+    fn insufficient_material_is_detected() {
+        for (fen, is_insufficient_material) in [
+            ("8/8/4k3/8/8/3K4/8/8 w - - 0 1", true),
+            ("8/8/4k3/8/8/3K4/8/5B2 w - - 0 1", true),
+            ("8/8/4k3/8/8/3K4/8/5n2 w - - 0 1", true),
+            ("8/8/4k3/3b4/8/3K4/8/5B2 w - - 0 1", true),
+            ("8/8/4k3/2b5/8/3K4/8/5B2 w - - 0 1", false),
+            ("8/8/4k3/8/8/3K4/8/4NN2 w - - 0 1", false),
+            ("8/8/4k3/8/8/3K4/8/4NB2 w - - 0 1", false),
+            ("8/8/4k3/8/8/3K4/7P/8 w - - 0 1", false),
+            ("8/8/4k3/8/8/3K4/8/7R w - - 0 1", false),
+        ] {
+            let position: Position = FenString::from(fen).parse().unwrap();
+            assert_eq!(position.is_insufficient_material(), is_insufficient_material, "{fen}");
+        }
+    }
+
+    #[test]
+    fn threefold_repetition_is_detected() {
+        let mut position = Position::starting_position();
+        let mut key_history = Vec::new();
+        play_moves(&mut position, &mut key_history, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+        assert!(!position.is_threefold_repetition(&key_history));
+        assert!(!position.is_repetition(&key_history));
+        play_moves(&mut position, &mut key_history, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+        assert!(position.is_threefold_repetition(&key_history));
+        assert!(position.is_repetition(&key_history));
+    }
+
+    #[test]
+    fn twofold_repetition_is_detected_inside_search() {
+        let mut position = Position::starting_position();
+        let mut key_history = Vec::new();
+        play_moves(&mut position, &mut key_history, &["g1f3", "g8f6", "f3g1", "f6g8"]);
+        position.ply = 4;
+        assert!(position.is_repetition(&key_history));
+        position.ply = 3;
+        assert!(!position.is_repetition(&key_history));
+    }
+
+    #[test]
+    fn repetitions_are_only_scanned_since_the_last_irreversible_move() {
+        let mut position = Position::starting_position();
+        let mut key_history = Vec::new();
+        play_moves(&mut position, &mut key_history, &["g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8"]);
+        position.halfmove_clock = 4;
+        assert!(!position.is_threefold_repetition(&key_history));
+        assert!(!position.is_repetition(&key_history));
+    }
+}
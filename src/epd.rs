@@ -0,0 +1,172 @@
+use crate::{BitMove, EpdParseError, FenString, Legal, MoveGeneration, Position, ScoringMove, Uci};
+
+const FEN_FIELD_COUNT: usize = 4;
+
+// A test position in Extended Position Description, which is the first four fields of a fen
+// followed by operations: https://www.chessprogramming.org/Extended_Position_Description
+#[derive(Clone)]
+pub struct EpdRecord {
+    pub position: Position,
+    pub best_moves: Vec<BitMove>,
+    pub avoid_moves: Vec<BitMove>,
+    pub id_option: Option<String>,
+    pub comment_option: Option<String>,
+    pub mate_option: Option<u16>,
+}
+
+impl EpdRecord {
+    #[inline(always)]
+    pub fn has_solution(&self) -> bool {
+        !self.best_moves.is_empty() || !self.avoid_moves.is_empty() || self.mate_option.is_some()
+    }
+
+    // NOTE: A move solves the position if it's one of the best moves, isn't one of the moves to
+    // avoid, and mates at least as fast as the direct mate, if any of those are given
+    pub fn is_solved_by(&self, scoring_move: ScoringMove) -> bool {
+        let score = scoring_move.score;
+        (self.best_moves.is_empty() || self.best_moves.contains(&scoring_move.bit_move)) &&
+        !self.avoid_moves.contains(&scoring_move.bit_move) &&
+        self.mate_option.is_none_or(|mate| score.is_checkmate() && score.is_positive() && score.moves_to_mate() <= mate as i16)
+    }
+
+    // NOTE: Semicolons only end operations outside of quoted strings, since comments may contain
+    // them. The last operation is accepted without one, since some suites leave it out.
+    fn split_operations(operations: &str) -> Result<Vec<(&str, &str)>, EpdParseError> {
+        let mut split_operations = Vec::new();
+        let mut start_index = 0;
+        let mut in_quotes = false;
+
+        for (index, c) in operations.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ';' if !in_quotes => {
+                    split_operations.push(&operations[start_index..index]);
+                    start_index = index + 1;
+                },
+                _ => (),
+            }
+        }
+
+        if in_quotes {
+            return Err(EpdParseError::Operation(operations[start_index..].trim().to_string()));
+        }
+
+        split_operations.push(&operations[start_index..]);
+
+        Ok(split_operations
+            .into_iter()
+            .map(str::trim)
+            .filter(|operation| !operation.is_empty())
+            .map(|operation| operation.split_once(char::is_whitespace).unwrap_or((operation, "")))
+            .map(|(opcode, operand)| (opcode, operand.trim()))
+            .collect())
+    }
+
+    fn parse_operand<T: std::str::FromStr>(opcode: &str, operand: &str) -> Result<T, EpdParseError> {
+        operand.parse().map_err(|_| EpdParseError::Operand(opcode.to_string(), operand.to_string()))
+    }
+
+    // NOTE: Moves are written in SAN, but some suites use UCI moves instead
+    fn parse_moves(position: &Position, operand: &str) -> Result<Vec<BitMove>, EpdParseError> {
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(position);
+        operand
+            .split_whitespace()
+            .map(|move_string| match position.parse_san(move_string) {
+                Ok(bit_move) => Ok(bit_move),
+                Err(error) => Uci::parse_move_string(&legal_moves, move_string).map_err(|_| error.into()),
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<&str> for EpdRecord {
+    type Error = EpdParseError;
+
+    // NOTE: The move counters are taken from the hmvc and fmvn operations if they're given
+    fn try_from(line: &str) -> Result<Self, Self::Error> {
+        let line = line.trim();
+        let mut fen_fields = Vec::new();
+        let mut operations = line;
+        for _ in 0..FEN_FIELD_COUNT {
+            let (fen_field, remaining) = operations.split_once(char::is_whitespace).unwrap_or((operations, ""));
+            if !fen_field.is_empty() {
+                fen_fields.push(fen_field);
+            }
+            operations = remaining.trim_start();
+        }
+
+        let operations = Self::split_operations(operations)?;
+        let get_operand = |opcode: &str| operations.iter().find(|(name, _)| *name == opcode).map(|&(_, operand)| operand);
+        let halfmove_clock: u16 = get_operand("hmvc").map_or(Ok(0), |operand| Self::parse_operand("hmvc", operand))?;
+        let fullmove_number: u16 = get_operand("fmvn").map_or(Ok(1), |operand| Self::parse_operand("fmvn", operand))?;
+        let mut fen = fen_fields.join(" ");
+        if fen_fields.len() == FEN_FIELD_COUNT {
+            fen.push_str(&format!(" {halfmove_clock} {fullmove_number}"));
+        }
+        let position: Position = FenString::from(fen).parse()?;
+
+        let unquote = |operand: &str| operand.trim_matches('"').to_string();
+        Ok(EpdRecord {
+            best_moves: get_operand("bm").map_or(Ok(Vec::new()), |operand| Self::parse_moves(&position, operand))?,
+            avoid_moves: get_operand("am").map_or(Ok(Vec::new()), |operand| Self::parse_moves(&position, operand))?,
+            id_option: get_operand("id").map(unquote),
+            comment_option: get_operand("c0").map(unquote),
+            mate_option: get_operand("dm").map(|operand| Self::parse_operand("dm", operand)).transpose()?,
+            position,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FenParseError, SanParseError, Score};
+
+    use super::*;
+
+    fn move_strings(bit_moves: &[BitMove]) -> Vec<String> {
+        bit_moves.iter().map(|bit_move| bit_move.to_uci_string()).collect()
+    }
+
+    #[test]
+    fn epd_records_are_parsed() {
+        let record = EpdRecord::try_from(r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001"; c0 "Mates; eventually";"#).unwrap();
+        assert_eq!(move_strings(&record.best_moves), ["g3g6"]);
+        assert!(record.avoid_moves.is_empty());
+        assert_eq!(record.id_option.as_deref(), Some("WAC.001"));
+        assert_eq!(record.comment_option.as_deref(), Some("Mates; eventually"));
+        assert_eq!(record.mate_option, None);
+        assert_eq!(record.position.fullmove_number, 1);
+
+        let record = EpdRecord::try_from("4k3/8/8/8/8/8/8/R3K2R w KQ - am O-O Ra2 e1d1; dm 3; hmvc 7; fmvn 42;").unwrap();
+        assert_eq!(move_strings(&record.avoid_moves), ["e1g1", "a1a2", "e1d1"]);
+        assert_eq!(record.mate_option, Some(3));
+        assert_eq!(record.position.halfmove_clock, 7);
+        assert_eq!(record.position.fullmove_number, 42);
+        assert!(record.has_solution());
+        assert!(!EpdRecord::try_from("4k3/8/8/8/8/8/8/R3K2R w KQ - id \"none\";").unwrap().has_solution());
+    }
+
+    #[test]
+    fn malformed_epd_records_are_rejected() {
+        assert!(matches!(EpdRecord::try_from("4k3/8/8/8/8/8/8/R3K2R w KQ"), Err(EpdParseError::FenParseError(FenParseError::NoEnPassant))));
+        assert_eq!(move_strings(&EpdRecord::try_from("4k3/8/8/8/8/8/8/R3K2R w KQ - bm Rh8+").unwrap().best_moves), ["h1h8"]);
+        assert!(matches!(EpdRecord::try_from("4k3/8/8/8/8/8/8/R3K2R w KQ - c0 \"open;"), Err(EpdParseError::Operation(_))));
+        assert!(matches!(EpdRecord::try_from("4k3/8/8/8/8/8/8/R3K2R w KQ - bm Rb8;"), Err(EpdParseError::SanParseError(SanParseError::IllegalMove(_)))));
+        assert!(matches!(EpdRecord::try_from("4k3/8/8/8/8/8/8/R3K2R w KQ - dm x;"), Err(EpdParseError::Operand(_, _))));
+    }
+
+    #[test]
+    fn epd_records_are_solved_by_best_moves_and_mates() {
+        let record = EpdRecord::try_from("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#; am Ra2; dm 1;").unwrap();
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&record.position);
+        let move_with_score = |move_string: &str, score: Score| ScoringMove::new(Uci::parse_move_string(&legal_moves, move_string).unwrap(), score);
+
+        assert!(record.is_solved_by(move_with_score("a1a8", Score::checkmate_minus_depth(1))));
+        assert!(!record.is_solved_by(move_with_score("a1a8", Score::from(500))));
+        assert!(!record.is_solved_by(move_with_score("a1a2", Score::checkmate_minus_depth(1))));
+
+        let record = EpdRecord::try_from("6k1/5ppp/8/8/8/8/8/R5K1 w - - am Ra2;").unwrap();
+        assert!(record.is_solved_by(move_with_score("a1a8", Score::ZERO)));
+        assert!(!record.is_solved_by(move_with_score("a1a2", Score::ZERO)));
+    }
+}
@@ -26,6 +26,12 @@ pub enum FenParseError {
     #[error("Couldn't parse fen en-passant square: {0}")]
     EnPassant(#[from] SquareParseError),
     
+    #[error("Couldn't parse fen halfmove clock: {0}")]
+    HalfmoveClock(String),
+    
+    #[error("Couldn't parse fen fullmove number: {0}")]
+    FullmoveNumber(String),
+    
     #[error("Couldn't parse illegal piece: {0}")]
     IllegalPiece(char),
 }
@@ -80,6 +86,63 @@ pub enum MoveStringParseError {
     SquareParseError(#[from] SquareParseError),
 }
 
+#[derive(Error, Debug)]
+pub enum SanParseError {
+    #[error("Empty SAN move string")]
+    Empty,
+
+    #[error("Couldn't parse SAN move string: {0}")]
+    Syntax(String),
+
+    #[error("Illegal promotion piece: {0}")]
+    PromotionPiece(String),
+
+    #[error("Couldn't find legal move: {0}")]
+    IllegalMove(String),
+
+    #[error("Ambiguous move: {0}")]
+    AmbiguousMove(String),
+
+    #[error("{0}")]
+    SquareParseError(#[from] SquareParseError),
+}
+
+#[derive(Error, Debug)]
+pub enum PgnParseError {
+    #[error("Couldn't read pgn: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Couldn't parse pgn tag on line {0}: {1}")]
+    Tag(usize, String),
+
+    #[error("Couldn't parse pgn fen on line {0}: {1}")]
+    Fen(usize, FenParseError),
+
+    #[error("Couldn't parse pgn move on line {0}: {1}")]
+    Move(usize, SanParseError),
+
+    #[error("Unexpected pgn token on line {0}: {1}")]
+    Token(usize, String),
+
+    #[error("Missing pgn result before line {0}")]
+    MissingResult(usize),
+}
+
+#[derive(Error, Debug)]
+pub enum EpdParseError {
+    #[error("Couldn't parse epd operation: {0}")]
+    Operation(String),
+
+    #[error("Couldn't parse epd operand of {0}: {1}")]
+    Operand(String, String),
+
+    #[error("{0}")]
+    FenParseError(#[from] FenParseError),
+
+    #[error("{0}")]
+    SanParseError(#[from] SanParseError),
+}
+
 #[derive(Error, Debug)]
 pub enum SquareParseError {
     #[error("Missing file character")]
@@ -105,4 +168,31 @@ pub enum BotGameError {
 
     #[error("Player performed illegal action")]
     IllegalActionError,
+
+    #[error("Game is already over")]
+    GameOverError,
+
+    #[error("No move to undo")]
+    NoMoveToUndoError,
+
+    #[error("No move to redo")]
+    NoMoveToRedoError,
+
+    #[error("{0}")]
+    MoveStringParseError(#[from] MoveStringParseError),
+
+    #[error("{0}")]
+    SanParseError(#[from] SanParseError),
+
+    #[error("{0}")]
+    FenParseError(#[from] FenParseError),
+}
+
+#[derive(Error, Debug)]
+pub enum OpeningBookError {
+    #[error("Couldn't read opening book file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Illegal opening book file length: {0}")]
+    FileLength(usize),
 }
@@ -1,7 +1,14 @@
-use crate::{BitMove, HistoryHeuristic, PIECE_TYPE_COUNT, KillerMoves, Position, Score, TTNodeType, TranspositionTable};
+use std::cmp::max;
+
+use crate::{BitMove, Bitboard, Color, MoveFlag, Piece, PIECE_TYPE_COUNT, Position, Score, SearchContext, Square, TTNodeType};
 
 #[allow(unused_imports)]
-use crate::{Color, MoveMasks, Piece, MoveFlag};
+use crate::MoveMasks;
+
+// NOTE: The king is given a finite value, since it can only capture as the last piece anyways
+const SEE_PIECE_SCORES: [i16; PIECE_TYPE_COUNT] = [100, 300, 300, 500, 900, 10000, 100, 300, 300, 500, 900, 10000];
+const SEE_MAX_EXCHANGES: usize = 32;
+const LOSING_CAPTURE_PENALTY: i16 = 3000;
 
 // Most valuable victim - least valuable attacker [attacker][victim]
 const MVV_LVA: [[i16; PIECE_TYPE_COUNT]; PIECE_TYPE_COUNT] = [
@@ -48,6 +55,12 @@ impl EvalMove {
         if let Some(capture) = capture_option {
             score += MVV_LVA[piece][capture];
 
+            // Losing captures are ordered after the quiet moves
+            #[cfg(feature = "see")]
+            if Self::see(position, bit_move) < Score::ZERO {
+                score -= LOSING_CAPTURE_PENALTY;
+            }
+
             #[cfg(feature = "capture_with_check_eval")]
             {
                 let enemy_king_bb = match position.side {
@@ -60,9 +73,102 @@ impl EvalMove {
             }
         };
 
+        score
+    }
+
+    // Static exchange evaluation of the material won or lost on the target square, assuming both
+    // sides keep recapturing with their least valuable attacker for as long as it pays off:
+    // https://www.chessprogramming.org/Static_Exchange_Evaluation
+    pub fn see(position: &Position, bit_move: BitMove) -> Score {
+        let source = bit_move.source();
+        let target = bit_move.target();
+        let mut occupancy = position.all_occupancy;
+        let mut gains = [0; SEE_MAX_EXCHANGES];
+        let mut piece_value = SEE_PIECE_SCORES[position.get_piece(source)];
+
+        if let Some(capture) = position.get_piece_option(target) {
+            gains[0] = SEE_PIECE_SCORES[capture];
+        }
+
+        match bit_move.flag_option() {
+            Some(MoveFlag::WEnPassant) => {
+                gains[0] = SEE_PIECE_SCORES[Piece::BP];
+                occupancy.pop_sq(target.below());
+            },
+            Some(MoveFlag::BEnPassant) => {
+                gains[0] = SEE_PIECE_SCORES[Piece::WP];
+                occupancy.pop_sq(target.above());
+            },
+            Some(flag @ (MoveFlag::PromoQ | MoveFlag::PromoR | MoveFlag::PromoB | MoveFlag::PromoN)) => {
+                let promotion_piece = match flag {
+                    MoveFlag::PromoQ => Piece::WQ,
+                    MoveFlag::PromoR => Piece::WR,
+                    MoveFlag::PromoB => Piece::WB,
+                    _ => Piece::WN,
+                };
+                piece_value = SEE_PIECE_SCORES[promotion_piece];
+                gains[0] += piece_value - SEE_PIECE_SCORES[Piece::WP];
+            },
+            _ => (),
+        }
+
+        occupancy.pop_sq(source);
+        let mut side = position.side.opposite();
+        let mut attackers = position.get_attackers(target, occupancy);
+        let mut depth = 0;
+        while depth + 1 < SEE_MAX_EXCHANGES {
+            let Some((attacker, attacker_square)) = Self::get_least_valuable_attacker(position, attackers, side) else {
+                break;
+            };
+
+            // The king can only recapture if the target square isn't defended anymore
+            let defenders = attackers & match side {
+                Color::White => position.black_occupancy,
+                Color::Black => position.white_occupancy,
+            };
+            if matches!(attacker, Piece::WK | Piece::BK) && defenders.is_not_empty() {
+                break;
+            }
+
+            depth += 1;
+            gains[depth] = piece_value - gains[depth - 1];
+            piece_value = SEE_PIECE_SCORES[attacker];
+            occupancy.pop_sq(attacker_square);
+            attackers = position.get_attackers(target, occupancy);
+            side = side.opposite();
+        }
+
+        while depth > 0 {
+            gains[depth - 1] = -max(-gains[depth - 1], gains[depth]);
+            depth -= 1;
+        }
+
+        Score::from(gains[0])
+    }
+
+    #[inline(always)]
+    fn get_least_valuable_attacker(position: &Position, attackers: Bitboard, side: Color) -> Option<(Piece, Square)> {
+        let pieces = match side {
+            Color::White => Piece::WHITE_PIECES,
+            Color::Black => Piece::BLACK_PIECES,
+        };
+
+        pieces.into_iter().find_map(|piece| {
+            let piece_attackers = position.bitboards[piece] & attackers;
+            piece_attackers.is_not_empty().then(|| (piece, piece_attackers.get_lsb()))
+        })
+    }
+
+    // NOTE: These scores depend on the state of a search, so they are added on top of the static
+    // move evaluation by the search itself.
+    #[allow(unused_variables, unused_mut)]
+    #[inline(always)]
+    pub(crate) fn eval_heuristics(position: &Position, bit_move: BitMove, context: &SearchContext) -> Score {
+        let mut score = Score::ZERO;
+
         #[cfg(feature = "eval_tt")]
         {
-            if let Some(tt_data) = TranspositionTable::probe(position.zobrist_key) {
+            if let Some(tt_data) = context.transposition_table.probe(position.zobrist_key) {
                 if tt_data.best_move.bit_move == bit_move {
                     match tt_data.node_type {
                         TTNodeType::Exact => score += 10000,
@@ -75,18 +181,59 @@ impl EvalMove {
 
         #[cfg(feature = "killer_heuristic")]
         {
-            if KillerMoves::get_primary(position.ply) == Some(bit_move) {
+            if context.killer_moves.get_primary(position.ply) == Some(bit_move) {
                 score += 2000;
-            } else if KillerMoves::get_secondary(position.ply) == Some(bit_move) {
+            } else if context.killer_moves.get_secondary(position.ply) == Some(bit_move) {
                 score += 1000;
             }
         }
 
         #[cfg(feature = "history_heuristic")]
         {
-            score += HistoryHeuristic::get(position.side, source, target);
+            score += context.history_heuristic.get(position.side, bit_move.source(), bit_move.target());
         }
 
         score
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{FenString, Legal, MoveGeneration, Uci};
+
+    use super::*;
+
+    fn see(fen: &str, move_string: &str) -> Score {
+        let position: Position = FenString::from(fen).parse().unwrap();
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&position);
+        EvalMove::see(&position, Uci::parse_move_string(&legal_moves, move_string).unwrap())
+    }
+
+    #[test]
+    fn see_wins_undefended_piece() {
+        assert_eq!(see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"), Score::from(100));
+    }
+
+    #[test]
+    fn see_loses_queen_for_pawn_defended_by_pawn() {
+        assert_eq!(see("4k3/8/3p4/4p3/8/8/8/4QK2 w - - 0 1", "e1e5"), Score::from(100 - 900));
+    }
+
+    #[test]
+    fn see_considers_x_ray_attackers() {
+        assert_eq!(see("1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1", "d3e5"), Score::from(100 - 300));
+        assert_eq!(see("4k3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), Score::from(100));
+    }
+
+    #[test]
+    fn see_only_lets_king_recapture_undefended_piece() {
+        assert_eq!(see("7k/3r4/8/8/8/8/8/3QK3 b - - 0 1", "d7d1"), Score::from(900 - 500));
+        assert_eq!(see("3r3k/3r4/8/8/8/8/8/3QK3 b - - 0 1", "d7d1"), Score::from(900));
+    }
+
+    #[test]
+    fn see_handles_en_passant_and_promotions() {
+        assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), Score::from(100));
+        assert_eq!(see("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1", "b7b8q"), Score::from(800));
+    }
+}
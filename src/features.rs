@@ -22,6 +22,12 @@ pub const FEATURES: &[&str] = &[
     "lazy_smp",
     "opening_book",
     "syzygy_tablebase",
+    "tt_buckets",
+    "pvs",
+    "aspiration_windows",
+    "see",
+    "move_picker",
+    "check_evasions",
 ];
 
 pub const BASE_FEATURES: &[&str] = &[
@@ -31,6 +37,8 @@ pub const BASE_FEATURES: &[&str] = &[
     "base_clone_parallel",
     "base_array",
     "base_array_parallel",
+    "base_legal",
+    "base_legal_parallel",
 ];
 
 pub const OTHER_FEATURES: &[&str] = &[
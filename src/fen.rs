@@ -33,11 +33,17 @@ impl FenString {
         let side_str = fen_iter.next().ok_or(FenParseError::NoSide)?;
         let castling_rights_str = fen_iter.next().ok_or(FenParseError::NoCastlingRights)?;
         let en_passant_sq_str = fen_iter.next().ok_or(FenParseError::NoEnPassant)?;
+
+        // NOTE: The move counters are optional, since many FEN strings leave them out
+        let halfmove_clock_str_option = fen_iter.next();
+        let fullmove_number_str_option = fen_iter.next();
         
         Self::set_pieces(&mut position, pieces_str)?;
         Self::set_side(&mut position, side_str)?;
         Self::set_castling_rights(&mut position, castling_rights_str)?;
         Self::set_en_passant_sq(&mut position, en_passant_sq_str)?;
+        Self::set_halfmove_clock(&mut position, halfmove_clock_str_option)?;
+        Self::set_fullmove_number(&mut position, fullmove_number_str_option)?;
         
         position.zobrist_key = ZobristKey::generate(&position);
 
@@ -100,6 +106,27 @@ impl FenString {
             }
         }
     }
+
+    fn set_halfmove_clock(position: &mut Position, halfmove_clock_str_option: Option<&str>) -> Result<(), FenParseError> {
+        if let Some(halfmove_clock_str) = halfmove_clock_str_option {
+            position.halfmove_clock = halfmove_clock_str
+                .parse()
+                .map_err(|_| FenParseError::HalfmoveClock(halfmove_clock_str.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    fn set_fullmove_number(position: &mut Position, fullmove_number_str_option: Option<&str>) -> Result<(), FenParseError> {
+        if let Some(fullmove_number_str) = fullmove_number_str_option {
+            position.fullmove_number = match fullmove_number_str.parse() {
+                Ok(0) | Err(_) => return Err(FenParseError::FullmoveNumber(fullmove_number_str.to_string())),
+                Ok(fullmove_number) => fullmove_number,
+            };
+        }
+
+        Ok(())
+    }
 }
 
 impl TryInto<Position> for FenString {
@@ -177,6 +204,8 @@ impl From<&Position> for FenString {
                 Some(en_passant_sq) => en_passant_sq.to_string(),
             }
         );
+
+        fen_str.push_str(&format!(" {} {}", position.halfmove_clock, position.fullmove_number));
         
         fen_str.into()
     }
@@ -187,3 +216,30 @@ impl fmt::Display for FenString {
         f.pad(&self.string)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fen_string_round_trips_all_fields() {
+        let fen_string = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 13 42";
+        let position = FenString::from(fen_string).parse().unwrap();
+        assert_eq!(position.halfmove_clock, 13);
+        assert_eq!(position.fullmove_number, 42);
+        assert_eq!(FenString::from(&position).to_string(), fen_string);
+    }
+
+    #[test]
+    fn fen_string_without_move_counters_uses_defaults() {
+        let position = FenString::startpos().parse().unwrap();
+        assert_eq!(position.halfmove_clock, 0);
+        assert_eq!(position.fullmove_number, 1);
+    }
+
+    #[test]
+    fn fen_string_with_illegal_move_counters_fails() {
+        assert!(FenString::from("8/8/8/8/8/8/8/K6k w - - x 1").parse().is_err());
+        assert!(FenString::from("8/8/8/8/8/8/8/K6k w - - 0 0").parse().is_err());
+    }
+}
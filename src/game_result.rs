@@ -0,0 +1,56 @@
+use std::fmt::Display;
+
+use crate::Color;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameTermination {
+    Checkmate,
+    Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    InsufficientMaterial,
+    Resignation,
+    DrawAgreement,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum GameResult {
+    Ongoing,
+    Win(Color, GameTermination),
+    Draw(GameTermination),
+}
+
+impl GameResult {
+    #[inline(always)]
+    pub fn is_over(self) -> bool {
+        self != GameResult::Ongoing
+    }
+
+    #[inline(always)]
+    pub fn winner(self) -> Option<Color> {
+        match self {
+            GameResult::Win(color, _) => Some(color),
+            _ => None,
+        }
+    }
+
+    #[inline(always)]
+    pub fn termination(self) -> Option<GameTermination> {
+        match self {
+            GameResult::Ongoing => None,
+            GameResult::Win(_, termination) | GameResult::Draw(termination) => Some(termination),
+        }
+    }
+}
+
+// NOTE: Results are displayed the way they are written at the end of a game in PGN
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            GameResult::Ongoing => "*",
+            GameResult::Win(Color::White, _) => "1-0",
+            GameResult::Win(Color::Black, _) => "0-1",
+            GameResult::Draw(_) => "1/2-1/2",
+        })
+    }
+}
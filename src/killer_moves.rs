@@ -1,46 +1,41 @@
-use std::mem;
-
 use crate::{BitMove, MAX_DEPTH};
 
-static mut PRIMARY_KILLER_MOVES: [BitMove; MAX_DEPTH] = unsafe { mem::zeroed() };
-static mut SECONDARY_KILLER_MOVES: [BitMove; MAX_DEPTH] = unsafe { mem::zeroed() };
+#[derive(Clone)]
+pub(crate) struct KillerMoves {
+    primary: [BitMove; MAX_DEPTH],
+    secondary: [BitMove; MAX_DEPTH],
+}
 
-pub(crate) struct KillerMoves;
+impl Default for KillerMoves {
+    fn default() -> KillerMoves {
+        KillerMoves {
+            primary: [BitMove::EMPTY; MAX_DEPTH],
+            secondary: [BitMove::EMPTY; MAX_DEPTH],
+        }
+    }
+}
 
 impl KillerMoves {
     #[inline(always)]
-    pub(crate) fn get_primary(ply: u16) -> Option<BitMove> {
-        if ply < MAX_DEPTH as u16 {
-            unsafe { Some(PRIMARY_KILLER_MOVES[ply as usize]) }
-        } else {
-            None
-        }
+    pub(crate) fn get_primary(&self, ply: u16) -> Option<BitMove> {
+        self.primary.get(ply as usize).copied()
     }
 
     #[inline(always)]
-    pub(crate) fn get_secondary(ply: u16) -> Option<BitMove> {
-        if ply < MAX_DEPTH as u16 {
-            unsafe { Some(SECONDARY_KILLER_MOVES[ply as usize]) }
-        } else {
-            None
-        }
+    pub(crate) fn get_secondary(&self, ply: u16) -> Option<BitMove> {
+        self.secondary.get(ply as usize).copied()
     }
 
     #[inline(always)]
-    pub(crate) fn update(bit_move: BitMove, ply: u16) {
+    pub(crate) fn update(&mut self, bit_move: BitMove, ply: u16) {
         if ply < MAX_DEPTH as u16 {
-            unsafe {
-                SECONDARY_KILLER_MOVES[ply as usize] = PRIMARY_KILLER_MOVES[ply as usize];
-                PRIMARY_KILLER_MOVES[ply as usize] = bit_move;
-            }
+            self.secondary[ply as usize] = self.primary[ply as usize];
+            self.primary[ply as usize] = bit_move;
         }
     }
 
     #[inline(always)]
-    pub(crate) fn reset() {
-        unsafe {
-            PRIMARY_KILLER_MOVES = mem::zeroed();
-            SECONDARY_KILLER_MOVES = mem::zeroed();
-        }
+    pub(crate) fn reset(&mut self) {
+        *self = KillerMoves::default();
     }
 }
@@ -21,12 +21,16 @@ mod bot_game;
 mod castling_rights;
 mod color;
 mod consts;
+mod draw_detection;
+mod epd;
 mod error;
 mod eval_move;
 mod eval_position;
 mod features;
 mod fen;
 mod file;
+#[cfg(feature = "bot_game")]
+mod game_result;
 mod history_heuristic;
 mod killer_moves;
 mod magic_numbers;
@@ -34,24 +38,38 @@ mod move_flag;
 mod move_generation;
 mod move_list;
 mod move_masks;
+#[cfg(feature = "move_picker")]
+mod move_picker;
 #[cfg(feature = "opening_book")]
 mod opening_book;
 mod perft;
+mod pgn;
 mod piece;
+#[cfg(feature = "opening_book")]
+mod polyglot;
 mod position;
+mod pv_line;
+mod pv_table;
 mod rank;
 mod rng;
+mod san;
 mod score;
 mod search;
+mod search_context;
+mod search_limits;
+mod skill;
 mod square;
+#[cfg(feature = "opening_book")]
+mod stats_book;
 #[cfg(feature = "syzygy_tablebase")]
 mod syzygy;
+mod time_manager;
 mod timer;
 mod transposition_table;
 mod uci;
 mod zobrist;
 #[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
-mod global_thread_pool;
+mod thread_pool;
 
 ///*--------------------------------*\\\
 //    PUBLIC LIBRARY FUNCTIONALITY    \\
@@ -61,22 +79,31 @@ pub use bit_move::{Move, BitMove, ScoringMove};
 pub use bot_game::BotGame;
 pub use castling_rights::CastlingRights;
 pub use color::Color;
+pub use epd::EpdRecord;
 pub use error::*;
 pub use eval_move::EvalMove;
 pub use eval_position::EvalPosition;
 pub use features::{BASE_FEATURES, FEATURES, OTHER_FEATURES};
 pub use fen::FenString;
 pub use file::File;
+#[cfg(feature = "bot_game")]
+pub use game_result::{GameResult, GameTermination};
 pub use move_flag::MoveFlag;
 pub use move_generation::{Legal, Filter, MoveGeneration, PseudoLegal};
 pub use move_list::MoveList;
 pub use perft::Perft;
+pub use pgn::{PgnGame, PgnReader};
 pub use piece::Piece;
 pub use position::Position;
+pub use pv_line::PvLine;
 pub use rank::Rank;
 pub use score::Score;
 pub use search::Search;
+pub use search_limits::SearchLimits;
 pub use square::Square;
+#[cfg(feature = "opening_book")]
+pub use stats_book::StatsBookBuilder;
+pub use time_manager::TimeManager;
 pub use timer::Timer;
 pub use uci::Uci;
 pub use zobrist::ZobristKey;
@@ -88,29 +115,38 @@ pub use zobrist::ZobristKey;
 //\*--------------------------------*/\\
 use bitboard::Bitboard;
 use consts::*;
-#[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
-use global_thread_pool::GlobalThreadPool;
 use history_heuristic::HistoryHeuristic;
 use killer_moves::KillerMoves;
 use move_masks::MoveMasks;
+#[cfg(feature = "move_picker")]
+use move_picker::MovePicker;
+#[cfg(feature = "opening_book")]
+use opening_book::{MoveStats, OpeningBook};
 #[cfg(feature = "opening_book")]
-use opening_book::OpeningBook;
+use polyglot::PolyglotBook;
+use pv_table::PvTable;
 use rng::RandomNumberGenerator;
+use search_context::SearchContext;
+use skill::Skill;
+#[cfg(feature = "opening_book")]
+use stats_book::{StatsBook, STATS_BOOK_EXTENSION};
 #[cfg(feature = "syzygy_tablebase")]
 use syzygy::SyzygyTablebase;
+#[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
+use thread_pool::ThreadPool;
 use transposition_table::{TranspositionTable, TTNodeType, TTData};
 
 ///*--------------------------------*\\\
 //      AUTO-INIT FUNCTIONALITY       \\
 //\*--------------------------------*/\\
+/// # Safety
+///
+/// This function initializes global lookup tables and must be called before any other
+/// functionality is used. It is called automatically with ctor on non-WASM targets.
 pub unsafe fn init() {
     MoveMasks::init_move_masks();
     EvalPosition::init_positional_masks();
     ZobristKey::init_zobrist_keys();
-    TranspositionTable::init();
-
-    #[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
-    GlobalThreadPool::init();
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -1,4 +1,4 @@
-use crate::{BitMove, Move, Bitboard, Color, MoveFlag, MoveList, MoveMasks, Piece, Position, Rank, Square};
+use crate::{BitMove, Move, Bitboard, Color, MoveFlag, MoveList, MoveMasks, Piece, Position, Rank, Square, SQUARE_COUNT};
 
 pub struct MoveGeneration;
 
@@ -305,12 +305,539 @@ impl MoveGeneration {
         move_list
     }
 
+    #[inline]
+    pub fn generate_quiets<T: Move, F: Filter>(position: &Position) -> MoveList<T> {
+        let mut move_list = MoveList::new();
+
+        let [pawn, knight, bishop, rook, queen, king] = match position.side {
+            Color::White => Piece::WHITE_PIECES,
+            Color::Black => Piece::BLACK_PIECES,
+        };
+        let empty_squares = !position.all_occupancy;
+
+        {
+            /*------------------------------*\ 
+                        Pawn moves
+            \*------------------------------*/
+            let (pawn_promotion_rank, pawn_starting_rank, pawn_double_push_rank, double_pawn_flag) = match position.side {
+                Color::White => (Rank::R7, Rank::R2, Rank::R4, Some(MoveFlag::WDoublePawn)),
+                Color::Black => (Rank::R2, Rank::R7, Rank::R5, Some(MoveFlag::BDoublePawn)),
+            };
+
+            let mut pawn_bb = position.bitboards[pawn];
+            while pawn_bb.is_not_empty() {
+                let source = pawn_bb.pop_lsb();
+                let source_rank = source.rank();
+
+                // Quiet moves
+                let mut quiet_mask = MoveMasks::get_pawn_quiet_mask(position.side, source) & empty_squares;
+                while quiet_mask.is_not_empty() {
+                    let target = quiet_mask.pop_lsb();
+                    
+                    if source_rank == pawn_starting_rank && target.rank() == pawn_double_push_rank {
+                        // Making sure both squares in front of the pawn are empty
+                        if (MoveMasks::get_pawn_quiet_mask(position.side, source) & position.all_occupancy).is_empty() {
+                            
+                            #[cfg(feature = "bb")]
+                            Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, pawn, None, double_pawn_flag));
+
+                            #[cfg(feature = "bb_array")]
+                            Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, double_pawn_flag));
+                        } 
+                    } else if source_rank == pawn_promotion_rank {
+                        #[cfg(feature = "bb")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, pawn, None, Some(MoveFlag::PromoN)));
+
+                        #[cfg(feature = "bb_array")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, Some(MoveFlag::PromoN)));
+                        
+                        #[cfg(feature = "bb")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, pawn, None, Some(MoveFlag::PromoB)));
+
+                        #[cfg(feature = "bb_array")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, Some(MoveFlag::PromoB)));
+                        
+                        #[cfg(feature = "bb")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, pawn, None, Some(MoveFlag::PromoR)));
+
+                        #[cfg(feature = "bb_array")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, Some(MoveFlag::PromoR)));
+                        
+                        #[cfg(feature = "bb")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, pawn, None, Some(MoveFlag::PromoQ)));
+
+                        #[cfg(feature = "bb_array")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, Some(MoveFlag::PromoQ)));
+                    } else {
+                        #[cfg(feature = "bb")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, pawn, None, None));
+
+                        #[cfg(feature = "bb_array")]
+                        Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, None));
+                    }
+                }
+            }
+        }
+
+        {
+            /*------------------------------*\ 
+                    Knight moves
+            \*------------------------------*/
+            let mut knight_bb = position.bitboards[knight];
+            while knight_bb.is_not_empty() {
+                let source = knight_bb.pop_lsb();
+                
+                let mut move_mask = MoveMasks::get_knight_mask(source) & empty_squares;
+                while move_mask.is_not_empty() {
+                    let target = move_mask.pop_lsb();
+
+                    #[cfg(feature = "bb")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, knight, None, None));
+
+                    #[cfg(feature = "bb_array")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, None));
+                }
+            }
+        }
+
+        {
+            /*------------------------------*\ 
+                        King moves
+            \*------------------------------*/
+            let (
+                king_side_castling_flag, queen_side_castling_flag,
+                king_side_castling_mask, queen_side_castling_mask,
+                king_side_castling_right, queen_side_castling_right,
+                castling_square_c, castling_square_d, castling_square_e, castling_square_f, castling_square_g
+            ) = match position.side {
+                Color::White => (
+                    Some(MoveFlag::WKCastle), Some(MoveFlag::WQCastle),
+                    Bitboard::W_KING_SIDE_MASK, Bitboard::W_QUEEN_SIDE_MASK,
+                    position.castling_rights.wk(), position.castling_rights.wq(),
+                    Square::C1, Square::D1, Square::E1, Square::F1, Square::G1
+                ),
+                Color::Black => (
+                    Some(MoveFlag::BKCastle), Some(MoveFlag::BQCastle),
+                    Bitboard::B_KING_SIDE_MASK, Bitboard::B_QUEEN_SIDE_MASK,
+                    position.castling_rights.bk(), position.castling_rights.bq(),
+                    Square::C8, Square::D8, Square::E8, Square::F8, Square::G8
+                ),
+            };
+
+            let mut king_bb = position.bitboards[king];
+            let source = king_bb.pop_lsb();
+            let mut move_mask = MoveMasks::get_king_mask(source) & empty_squares;
+            while move_mask.is_not_empty() {
+                let target = move_mask.pop_lsb();
+
+                #[cfg(feature = "bb")]
+                Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, king, None, None));
+
+                #[cfg(feature = "bb_array")]
+                Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, None));
+            }
+
+            // Kingside Castling
+            #[allow(clippy::collapsible_if)]
+            if king_side_castling_right && (position.all_occupancy & king_side_castling_mask).is_empty() {
+                if !position.is_square_attacked(position.side, castling_square_e) &&
+                !position.is_square_attacked(position.side, castling_square_f) &&
+                !position.is_square_attacked(position.side, castling_square_g)
+                {
+                    
+                    #[cfg(feature = "bb")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, castling_square_g, king, None, king_side_castling_flag));
+
+                    #[cfg(feature = "bb_array")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, castling_square_g, king_side_castling_flag));
+                }
+            }
+
+            // Queenside Castling
+            #[allow(clippy::collapsible_if)]
+            if queen_side_castling_right && (position.all_occupancy & queen_side_castling_mask).is_empty() {
+                if !position.is_square_attacked(position.side, castling_square_e) &&
+                !position.is_square_attacked(position.side, castling_square_d) &&
+                !position.is_square_attacked(position.side, castling_square_c)
+                {
+                    
+                    #[cfg(feature = "bb")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, castling_square_c, king, None, queen_side_castling_flag));
+
+                    #[cfg(feature = "bb_array")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, castling_square_c, queen_side_castling_flag));
+                }
+            }
+        }
+
+        {
+            /*------------------------------*\ 
+                    Bishop moves
+            \*------------------------------*/
+            let mut bishop_bb = position.bitboards[bishop];
+            while bishop_bb.is_not_empty() {
+                let source = bishop_bb.pop_lsb();
+                let mut move_mask = MoveMasks::get_bishop_mask(source, position.all_occupancy) & empty_squares;
+                while move_mask.is_not_empty() {
+                    let target = move_mask.pop_lsb();
+
+                    #[cfg(feature = "bb")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, bishop, None, None));
+
+                    #[cfg(feature = "bb_array")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, None));
+                }
+            }
+        }
+
+        {
+            /*------------------------------*\ 
+                        Rook moves
+            \*------------------------------*/
+            let mut rook_bb = position.bitboards[rook];
+            while rook_bb.is_not_empty() {
+                let source = rook_bb.pop_lsb();
+                let mut move_mask = MoveMasks::get_rook_mask(source, position.all_occupancy) & empty_squares;
+                while move_mask.is_not_empty() {
+                    let target = move_mask.pop_lsb();
+
+                    #[cfg(feature = "bb")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, rook, None, None));
+
+                    #[cfg(feature = "bb_array")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, None));
+                }
+            }
+        }
+
+        {
+            /*------------------------------*\ 
+                    Queen moves
+            \*------------------------------*/
+            let mut queen_bb = position.bitboards[queen];
+            while queen_bb.is_not_empty() {
+                let source = queen_bb.pop_lsb();
+                let mut move_mask = MoveMasks::get_queen_mask(source, position.all_occupancy) & empty_squares;
+                while move_mask.is_not_empty() {
+                    let target = move_mask.pop_lsb();
+
+                    #[cfg(feature = "bb")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, queen, None, None));
+
+                    #[cfg(feature = "bb_array")]
+                    Self::add_move::<T, F>(position, &mut move_list, BitMove::encode(source, target, None));
+                }
+            }
+        }
+        
+        move_list
+    }
+
     fn add_move<T: Move, F: Filter>(position: &Position, move_list: &mut MoveList<T>, bit_move: BitMove) {
         if F::should_add(position, bit_move) {
             move_list.add(T::new(position, bit_move));
         }
     }
 
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn add_piece_move<T: Move, F: Filter>(position: &Position, move_list: &mut MoveList<T>, source: Square, target: Square, piece: Piece, flag_option: Option<MoveFlag>) {
+        #[cfg(feature = "bb")]
+        Self::add_move::<T, F>(position, move_list, BitMove::encode(source, target, piece, position.get_piece_option(target), flag_option));
+
+        #[cfg(feature = "bb_array")]
+        Self::add_move::<T, F>(position, move_list, BitMove::encode(source, target, flag_option));
+    }
+
+    // NOTE: Only king moves, captures of the checking piece and interpositions between the king
+    // and a checking slider can get the king out of check. In double check, only the king can move.
+    #[inline]
+    pub fn generate_evasions<T: Move, F: Filter>(position: &Position) -> MoveList<T> {
+        let mut move_list = MoveList::new();
+
+        let ([pawn, knight, bishop, rook, queen, king], own_occupancies, enemy_occupancies) = match position.side {
+            Color::White => (Piece::WHITE_PIECES, position.white_occupancy, position.black_occupancy),
+            Color::Black => (Piece::BLACK_PIECES, position.black_occupancy, position.white_occupancy),
+        };
+
+        let king_square = Square::from(position.bitboards[king]);
+        let checkers = position.get_attackers(king_square, position.all_occupancy) & enemy_occupancies;
+        if checkers.is_empty() {
+            return Self::generate_moves::<T, F>(position);
+        }
+
+        {
+            /*------------------------------*\ 
+                        King moves
+            \*------------------------------*/
+            let mut move_mask = MoveMasks::get_king_mask(king_square) & !own_occupancies;
+            while move_mask.is_not_empty() {
+                let target = move_mask.pop_lsb();
+                Self::add_piece_move::<T, F>(position, &mut move_list, king_square, target, king, None);
+            }
+        }
+
+        if checkers.count_bits() > 1 {
+            return move_list;
+        }
+
+        let checker_square = checkers.get_lsb();
+        let block_mask = Self::get_between_mask(king_square, checker_square, position.all_occupancy);
+        let evasion_mask = checkers | block_mask;
+
+        {
+            /*------------------------------*\ 
+                        Pawn moves
+            \*------------------------------*/
+            let (pawn_promotion_rank, pawn_starting_rank, en_passant_rank, pawn_double_push_rank, double_pawn_flag, en_passant_flag) = match position.side {
+                Color::White => (Rank::R7, Rank::R2, Rank::R5, Rank::R4, Some(MoveFlag::WDoublePawn), Some(MoveFlag::WEnPassant)),
+                Color::Black => (Rank::R2, Rank::R7, Rank::R4, Rank::R5, Some(MoveFlag::BDoublePawn), Some(MoveFlag::BEnPassant)),
+            };
+
+            let mut pawn_bb = position.bitboards[pawn];
+            while pawn_bb.is_not_empty() {
+                let source = pawn_bb.pop_lsb();
+                let source_rank = source.rank();
+
+                // Captures of the checker and interpositions
+                let capture_mask = MoveMasks::get_pawn_capture_mask(position.side, source) & checkers;
+                let mut move_mask = capture_mask | (MoveMasks::get_pawn_quiet_mask(position.side, source) & !position.all_occupancy & block_mask);
+                while move_mask.is_not_empty() {
+                    let target = move_mask.pop_lsb();
+
+                    if source_rank == pawn_promotion_rank {
+                        for promotion_flag in [MoveFlag::PromoN, MoveFlag::PromoB, MoveFlag::PromoR, MoveFlag::PromoQ] {
+                            Self::add_piece_move::<T, F>(position, &mut move_list, source, target, pawn, Some(promotion_flag));
+                        }
+                    } else if source_rank == pawn_starting_rank && target.rank() == pawn_double_push_rank {
+                        // Making sure both squares in front of the pawn are empty
+                        if (MoveMasks::get_pawn_quiet_mask(position.side, source) & position.all_occupancy).is_empty() {
+                            Self::add_piece_move::<T, F>(position, &mut move_list, source, target, pawn, double_pawn_flag);
+                        }
+                    } else {
+                        Self::add_piece_move::<T, F>(position, &mut move_list, source, target, pawn, None);
+                    }
+                }
+
+                // En-passant, which can only capture a checking pawn that just moved two squares
+                if let Some(en_passant_sq) = position.en_passant_option {
+                    let captured_square = match position.side {
+                        Color::White => en_passant_sq.below(),
+                        Color::Black => en_passant_sq.above(),
+                    };
+
+                    if source_rank == en_passant_rank &&
+                    MoveMasks::get_pawn_capture_mask(position.side, source).is_set_sq(en_passant_sq) &&
+                    (checkers.is_set_sq(captured_square) || block_mask.is_set_sq(en_passant_sq))
+                    {
+                        Self::add_piece_move::<T, F>(position, &mut move_list, source, en_passant_sq, pawn, en_passant_flag);
+                    }
+                }
+            }
+        }
+
+        {
+            /*------------------------------*\ 
+                    Piece moves
+            \*------------------------------*/
+            for piece in [knight, bishop, rook, queen] {
+                let mut piece_bb = position.bitboards[piece];
+                while piece_bb.is_not_empty() {
+                    let source = piece_bb.pop_lsb();
+                    let mut move_mask = MoveMasks::get_piece_mask(piece, source, position.all_occupancy) & evasion_mask;
+                    while move_mask.is_not_empty() {
+                        let target = move_mask.pop_lsb();
+                        Self::add_piece_move::<T, F>(position, &mut move_list, source, target, piece, None);
+                    }
+                }
+            }
+        }
+
+        move_list
+    }
+
+    // Generates only legal moves without making them, by restricting the moves of all pieces to
+    // the check mask and the moves of pinned pieces to their pin ray up front:
+    // https://www.chessprogramming.org/Move_Generation#Legal
+    #[inline]
+    pub fn generate_legal_moves<T: Move>(position: &Position) -> MoveList<T> {
+        let mut move_list = MoveList::new();
+
+        let ([pawn, knight, bishop, rook, queen, king], [_, _, enemy_bishop, enemy_rook, enemy_queen, _], own_occupancies, enemy_occupancies) = match position.side {
+            Color::White => (Piece::WHITE_PIECES, Piece::BLACK_PIECES, position.white_occupancy, position.black_occupancy),
+            Color::Black => (Piece::BLACK_PIECES, Piece::WHITE_PIECES, position.black_occupancy, position.white_occupancy),
+        };
+
+        let king_square = Square::from(position.bitboards[king]);
+        let checkers = position.get_attackers(king_square, position.all_occupancy) & enemy_occupancies;
+
+        {
+            /*------------------------------*\ 
+                        King moves
+            \*------------------------------*/
+            let occupancy_without_king = position.all_occupancy & !position.bitboards[king];
+            let mut move_mask = MoveMasks::get_king_mask(king_square) & !own_occupancies;
+            while move_mask.is_not_empty() {
+                let target = move_mask.pop_lsb();
+                if (position.get_attackers(target, occupancy_without_king) & enemy_occupancies).is_empty() {
+                    Self::add_piece_move::<T, PseudoLegal>(position, &mut move_list, king_square, target, king, None);
+                }
+            }
+
+            if checkers.is_empty() {
+                let (
+                    king_side_castling_flag, queen_side_castling_flag,
+                    king_side_castling_mask, queen_side_castling_mask,
+                    king_side_castling_right, queen_side_castling_right,
+                    castling_square_c, castling_square_d, castling_square_f, castling_square_g
+                ) = match position.side {
+                    Color::White => (
+                        Some(MoveFlag::WKCastle), Some(MoveFlag::WQCastle),
+                        Bitboard::W_KING_SIDE_MASK, Bitboard::W_QUEEN_SIDE_MASK,
+                        position.castling_rights.wk(), position.castling_rights.wq(),
+                        Square::C1, Square::D1, Square::F1, Square::G1
+                    ),
+                    Color::Black => (
+                        Some(MoveFlag::BKCastle), Some(MoveFlag::BQCastle),
+                        Bitboard::B_KING_SIDE_MASK, Bitboard::B_QUEEN_SIDE_MASK,
+                        position.castling_rights.bk(), position.castling_rights.bq(),
+                        Square::C8, Square::D8, Square::F8, Square::G8
+                    ),
+                };
+
+                // Kingside Castling
+                if king_side_castling_right && (position.all_occupancy & king_side_castling_mask).is_empty() &&
+                !position.is_square_attacked(position.side, castling_square_f) &&
+                !position.is_square_attacked(position.side, castling_square_g)
+                {
+                    Self::add_piece_move::<T, PseudoLegal>(position, &mut move_list, king_square, castling_square_g, king, king_side_castling_flag);
+                }
+
+                // Queenside Castling
+                if queen_side_castling_right && (position.all_occupancy & queen_side_castling_mask).is_empty() &&
+                !position.is_square_attacked(position.side, castling_square_d) &&
+                !position.is_square_attacked(position.side, castling_square_c)
+                {
+                    Self::add_piece_move::<T, PseudoLegal>(position, &mut move_list, king_square, castling_square_c, king, queen_side_castling_flag);
+                }
+            }
+        }
+
+        // In double check, only the king can move
+        let check_mask = match checkers.count_bits() {
+            0 => !Bitboard::EMPTY,
+            1 => checkers | Self::get_between_mask(king_square, checkers.get_lsb(), position.all_occupancy),
+            _ => return move_list,
+        };
+
+        // Own pieces that are the only blocker between the king and an enemy slider are pinned,
+        // so they can only move along the ray towards the pinning piece
+        let mut pinned = Bitboard::EMPTY;
+        let mut pin_rays = [Bitboard::EMPTY; SQUARE_COUNT];
+        let mut snipers =
+            (MoveMasks::get_bishop_mask(king_square, enemy_occupancies) & (position.bitboards[enemy_bishop] | position.bitboards[enemy_queen])) |
+            (MoveMasks::get_rook_mask(king_square, enemy_occupancies) & (position.bitboards[enemy_rook] | position.bitboards[enemy_queen]));
+        while snipers.is_not_empty() {
+            let sniper_square = snipers.pop_lsb();
+            let pin_ray = Self::get_between_mask(king_square, sniper_square, king_square.to_bb() | sniper_square.to_bb());
+            let blockers = pin_ray & position.all_occupancy;
+            if blockers.count_bits() == 1 && (blockers & own_occupancies).is_not_empty() {
+                pinned |= blockers;
+                pin_rays[blockers.get_lsb()] = pin_ray | sniper_square.to_bb();
+            }
+        }
+
+        let get_allowed_mask = |source: Square| if pinned.is_set_sq(source) { check_mask & pin_rays[source] } else { check_mask };
+
+        {
+            /*------------------------------*\ 
+                        Pawn moves
+            \*------------------------------*/
+            let (pawn_promotion_rank, pawn_starting_rank, en_passant_rank, pawn_double_push_rank, double_pawn_flag, en_passant_flag) = match position.side {
+                Color::White => (Rank::R7, Rank::R2, Rank::R5, Rank::R4, Some(MoveFlag::WDoublePawn), Some(MoveFlag::WEnPassant)),
+                Color::Black => (Rank::R2, Rank::R7, Rank::R4, Rank::R5, Some(MoveFlag::BDoublePawn), Some(MoveFlag::BEnPassant)),
+            };
+
+            let mut pawn_bb = position.bitboards[pawn];
+            while pawn_bb.is_not_empty() {
+                let source = pawn_bb.pop_lsb();
+                let source_rank = source.rank();
+                let allowed_mask = get_allowed_mask(source);
+
+                let capture_mask = MoveMasks::get_pawn_capture_mask(position.side, source) & enemy_occupancies;
+                let mut move_mask = (capture_mask | (MoveMasks::get_pawn_quiet_mask(position.side, source) & !position.all_occupancy)) & allowed_mask;
+                while move_mask.is_not_empty() {
+                    let target = move_mask.pop_lsb();
+
+                    if source_rank == pawn_promotion_rank {
+                        for promotion_flag in [MoveFlag::PromoN, MoveFlag::PromoB, MoveFlag::PromoR, MoveFlag::PromoQ] {
+                            Self::add_piece_move::<T, PseudoLegal>(position, &mut move_list, source, target, pawn, Some(promotion_flag));
+                        }
+                    } else if source_rank == pawn_starting_rank && target.rank() == pawn_double_push_rank {
+                        // Making sure both squares in front of the pawn are empty
+                        if (MoveMasks::get_pawn_quiet_mask(position.side, source) & position.all_occupancy).is_empty() {
+                            Self::add_piece_move::<T, PseudoLegal>(position, &mut move_list, source, target, pawn, double_pawn_flag);
+                        }
+                    } else {
+                        Self::add_piece_move::<T, PseudoLegal>(position, &mut move_list, source, target, pawn, None);
+                    }
+                }
+
+                // NOTE: En-passant removes two pawns from the same rank at once, which can
+                // uncover an attack on the king that no pin detects, so it's verified directly
+                if let Some(en_passant_sq) = position.en_passant_option {
+                    if source_rank == en_passant_rank && MoveMasks::get_pawn_capture_mask(position.side, source).is_set_sq(en_passant_sq) {
+                        let captured_square = match position.side {
+                            Color::White => en_passant_sq.below(),
+                            Color::Black => en_passant_sq.above(),
+                        };
+                        let occupancy = (position.all_occupancy & !source.to_bb() & !captured_square.to_bb()) | en_passant_sq.to_bb();
+
+                        if (position.get_attackers(king_square, occupancy) & enemy_occupancies).is_empty() {
+                            Self::add_piece_move::<T, PseudoLegal>(position, &mut move_list, source, en_passant_sq, pawn, en_passant_flag);
+                        }
+                    }
+                }
+            }
+        }
+
+        {
+            /*------------------------------*\ 
+                        Piece moves
+            \*------------------------------*/
+            for piece in [knight, bishop, rook, queen] {
+                let mut piece_bb = position.bitboards[piece];
+                while piece_bb.is_not_empty() {
+                    let source = piece_bb.pop_lsb();
+                    let mut move_mask = MoveMasks::get_piece_mask(piece, source, position.all_occupancy) & !own_occupancies & get_allowed_mask(source);
+                    while move_mask.is_not_empty() {
+                        let target = move_mask.pop_lsb();
+                        Self::add_piece_move::<T, PseudoLegal>(position, &mut move_list, source, target, piece, None);
+                    }
+                }
+            }
+        }
+
+        move_list
+    }
+
+    // NOTE: The attacks of two sliders on the same line only overlap between them, since their
+    // other lines are parallel to each other
+    #[inline(always)]
+    fn get_between_mask(king_square: Square, checker_square: Square, occupancy: Bitboard) -> Bitboard {
+        let bishop_mask = MoveMasks::get_bishop_mask(king_square, occupancy);
+        if bishop_mask.is_set_sq(checker_square) {
+            return bishop_mask & MoveMasks::get_bishop_mask(checker_square, occupancy);
+        }
+
+        let rook_mask = MoveMasks::get_rook_mask(king_square, occupancy);
+        if rook_mask.is_set_sq(checker_square) {
+            return rook_mask & MoveMasks::get_rook_mask(checker_square, occupancy);
+        }
+
+        Bitboard::EMPTY
+    }
+
     #[inline]
     pub fn generate_captures<T: Move, F: Filter>(position: &Position) -> MoveList<T> {
         let mut move_list = MoveList::new();
@@ -377,7 +904,6 @@ impl MoveGeneration {
                     }
                 }
 
-                #[cfg(feature = "quiescence_en_passant")]
                 // En-passant
                 if let Some(en_passant_sq) = position.en_passant_option {
                     if source_rank == en_passant_rank {
@@ -513,6 +1039,102 @@ impl MoveGeneration {
         
         move_list
     }
+
+    // NOTE: Quiet queen promotions are tried along with the captures by the move picker, so they
+    // aren't ordered after the killers and underpromotions of the quiet stage
+    #[cfg(feature = "move_picker")]
+    #[inline]
+    pub(crate) fn add_quiet_queen_promotions<T: Move, F: Filter>(position: &Position, move_list: &mut MoveList<T>) {
+        let (pawn, pawn_promotion_rank) = match position.side {
+            Color::White => (Piece::WP, Rank::R7),
+            Color::Black => (Piece::BP, Rank::R2),
+        };
+
+        let mut pawn_bb = position.bitboards[pawn];
+        while pawn_bb.is_not_empty() {
+            let source = pawn_bb.pop_lsb();
+            if source.rank() != pawn_promotion_rank {
+                continue;
+            }
+
+            let mut quiet_mask = MoveMasks::get_pawn_quiet_mask(position.side, source) & !position.all_occupancy;
+            while quiet_mask.is_not_empty() {
+                let target = quiet_mask.pop_lsb();
+
+                #[cfg(feature = "bb")]
+                Self::add_move::<T, F>(position, move_list, BitMove::encode(source, target, pawn, None, Some(MoveFlag::PromoQ)));
+
+                #[cfg(feature = "bb_array")]
+                Self::add_move::<T, F>(position, move_list, BitMove::encode(source, target, Some(MoveFlag::PromoQ)));
+            }
+        }
+    }
+
+    // NOTE: Checks a move that wasn't generated for this position, like a transposition table
+    // move after a key collision or a killer move from a sibling node, against the move masks
+    // of the piece on its source square, so it can be tried without generating all moves first
+    #[cfg(feature = "move_picker")]
+    pub(crate) fn is_pseudo_legal(position: &Position, bit_move: BitMove) -> bool {
+        let (source, target, flag_option) = bit_move.decode();
+        let Some(piece) = position.get_piece_option(source).filter(|piece| piece.color() == position.side) else {
+            return false;
+        };
+
+        let (pawn, king, own_occupancies, enemy_occupancies) = match position.side {
+            Color::White => (Piece::WP, Piece::WK, position.white_occupancy, position.black_occupancy),
+            Color::Black => (Piece::BP, Piece::BK, position.black_occupancy, position.white_occupancy),
+        };
+
+        if bit_move == BitMove::EMPTY || own_occupancies.is_set_sq(target) {
+            return false;
+        }
+
+        if piece == pawn {
+            let (pawn_promotion_rank, pawn_starting_rank, pawn_double_push_rank, double_pawn_flag, en_passant_flag) = match position.side {
+                Color::White => (Rank::R7, Rank::R2, Rank::R4, MoveFlag::WDoublePawn, MoveFlag::WEnPassant),
+                Color::Black => (Rank::R2, Rank::R7, Rank::R5, MoveFlag::BDoublePawn, MoveFlag::BEnPassant),
+            };
+
+            let quiet_mask = MoveMasks::get_pawn_quiet_mask(position.side, source);
+            let capture_mask = MoveMasks::get_pawn_capture_mask(position.side, source);
+            let is_push = quiet_mask.is_set_sq(target) && !position.all_occupancy.is_set_sq(target);
+            let is_capture = capture_mask.is_set_sq(target) && enemy_occupancies.is_set_sq(target);
+            let is_double_push = source.rank() == pawn_starting_rank && target.rank() == pawn_double_push_rank;
+            let is_promotion = source.rank() == pawn_promotion_rank;
+
+            return match flag_option {
+                None => !is_promotion && !is_double_push && (is_push || is_capture),
+                Some(flag) if flag.is_promotion() => is_promotion && (is_push || is_capture),
+                Some(flag) if flag == double_pawn_flag => is_double_push && (quiet_mask & position.all_occupancy).is_empty(),
+                Some(flag) if flag == en_passant_flag => position.en_passant_option == Some(target) && capture_mask.is_set_sq(target),
+                Some(_) => false,
+            };
+        }
+
+        if piece == king {
+            let castles = match position.side {
+                Color::White => [
+                    (MoveFlag::WKCastle, position.castling_rights.wk(), Bitboard::W_KING_SIDE_MASK, [Square::E1, Square::F1, Square::G1]),
+                    (MoveFlag::WQCastle, position.castling_rights.wq(), Bitboard::W_QUEEN_SIDE_MASK, [Square::E1, Square::D1, Square::C1]),
+                ],
+                Color::Black => [
+                    (MoveFlag::BKCastle, position.castling_rights.bk(), Bitboard::B_KING_SIDE_MASK, [Square::E8, Square::F8, Square::G8]),
+                    (MoveFlag::BQCastle, position.castling_rights.bq(), Bitboard::B_QUEEN_SIDE_MASK, [Square::E8, Square::D8, Square::C8]),
+                ],
+            };
+
+            return match flag_option {
+                None => MoveMasks::get_king_mask(source).is_set_sq(target),
+                Some(flag) => castles.iter().any(|&(castle_flag, castling_right, castling_mask, [king_square, passed_square, castling_square])| {
+                    flag == castle_flag && castling_right && source == king_square && target == castling_square &&
+                    (position.all_occupancy & castling_mask).is_empty() &&
+                    [king_square, passed_square, castling_square].iter().all(|&square| !position.is_square_attacked(position.side, square))
+                }),
+            };
+        }
+
+        flag_option.is_none() && MoveMasks::get_piece_mask(piece, source, position.all_occupancy).is_set_sq(target)
+    }
 }
 
 pub trait Filter {
@@ -541,6 +1163,8 @@ impl Filter for Legal {
 mod tests {
     use std::collections::HashSet;
 
+    use crate::FenString;
+
     use super::*;
 
     #[test]
@@ -549,4 +1173,32 @@ mod tests {
         let mut seen = HashSet::new();
         assert!(move_list.iter().all(|&m| seen.insert(m)));
     }
+
+    #[test]
+    #[cfg(feature = "move_picker")]
+    fn is_pseudo_legal_matches_generated_moves() {
+        let positions: Vec<Position> = [
+            FenString::startpos(),
+            FenString::kiwipete(),
+            FenString::rook(),
+            FenString::tricky(),
+            FenString::tricky2(),
+            FenString::from("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1"),
+            FenString::from("r3k2r/1P6/8/8/5b2/8/6p1/R3K2R b KQkq - 0 1"),
+        ].into_iter().map(|fen| fen.parse().unwrap()).collect();
+
+        // NOTE: Moves of the other positions act as moves after a key collision or as killers
+        let all_moves: HashSet<BitMove> = positions
+            .iter()
+            .flat_map(|position| MoveGeneration::generate_moves::<BitMove, PseudoLegal>(position).iter().copied().collect::<Vec<BitMove>>())
+            .collect();
+
+        for position in &positions {
+            let pseudo_legal_moves: HashSet<BitMove> = MoveGeneration::generate_moves::<BitMove, PseudoLegal>(position).iter().copied().collect();
+            for &bit_move in &all_moves {
+                assert_eq!(MoveGeneration::is_pseudo_legal(position, bit_move), pseudo_legal_moves.contains(&bit_move), "{} in {}", bit_move.to_uci_string(), FenString::from(position));
+            }
+            assert!(!MoveGeneration::is_pseudo_legal(position, BitMove::EMPTY));
+        }
+    }
 }
@@ -0,0 +1,322 @@
+use crate::{BitMove, MoveFlag, MoveGeneration, MoveList, Position, PseudoLegal, Score, ScoringMove, SearchContext};
+
+use crate::EvalMove;
+
+const KILLER_COUNT: usize = 2;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Stage {
+    TTMove,
+    GenerateEvasions,
+    Evasions,
+    GenerateCaptures,
+    GoodCaptures,
+    Killers,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,
+    Done,
+}
+
+// Yields the pseudo-legal moves of a position in stages, so the moves after a beta cutoff don't
+// have to be generated or scored: https://www.chessprogramming.org/Move_Generation#Staged_Move_Generation
+pub(crate) struct MovePicker<'a> {
+    position: &'a Position,
+    stage: Stage,
+    in_check: bool,
+    tt_move: BitMove,
+    killers: [BitMove; KILLER_COUNT],
+    killer_index: usize,
+    captures: MoveList<ScoringMove>,
+    bad_captures: MoveList<ScoringMove>,
+    quiets: MoveList<ScoringMove>,
+    index: usize,
+}
+
+impl<'a> MovePicker<'a> {
+    // NOTE: In check, all evasions are picked in a single stage after the TT move
+    #[inline(always)]
+    pub(crate) fn new(position: &'a Position, tt_move: BitMove, in_check: bool) -> MovePicker<'a> {
+        MovePicker {
+            position,
+            stage: Stage::TTMove,
+            in_check,
+            tt_move,
+            killers: [BitMove::EMPTY; KILLER_COUNT],
+            killer_index: 0,
+            captures: MoveList::new(),
+            bad_captures: MoveList::new(),
+            quiets: MoveList::new(),
+            index: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn next(&mut self, context: &SearchContext) -> Option<ScoringMove> {
+        loop {
+            match self.stage {
+                Stage::TTMove => {
+                    self.stage = if self.in_check { Stage::GenerateEvasions } else { Stage::GenerateCaptures };
+                    if self.is_tt_move_valid() {
+                        return Some(ScoringMove::new(self.tt_move, Score::ZERO));
+                    }
+                    self.tt_move = BitMove::EMPTY;
+                },
+                Stage::GenerateEvasions => {
+                    // NOTE: Evasions reuse the capture list, since captures are never generated in check
+                    self.captures = MoveGeneration::generate_evasions::<ScoringMove, PseudoLegal>(self.position);
+                    for scoring_move in self.captures.iter_mut() {
+                        scoring_move.score += EvalMove::eval_heuristics(self.position, scoring_move.bit_move, context);
+                    }
+                    self.index = 0;
+                    self.stage = Stage::Evasions;
+                },
+                Stage::Evasions => {
+                    let Some(scoring_move) = Self::select_best(&mut self.captures, &mut self.index) else {
+                        self.stage = Stage::Done;
+                        continue;
+                    };
+
+                    if scoring_move.bit_move != self.tt_move {
+                        return Some(scoring_move);
+                    }
+                },
+                Stage::GenerateCaptures => {
+                    self.captures = MoveGeneration::generate_captures::<ScoringMove, PseudoLegal>(self.position);
+                    MoveGeneration::add_quiet_queen_promotions::<ScoringMove, PseudoLegal>(self.position, &mut self.captures);
+                    self.index = 0;
+                    self.stage = Stage::GoodCaptures;
+                },
+                Stage::GoodCaptures => {
+                    let Some(scoring_move) = Self::select_best(&mut self.captures, &mut self.index) else {
+                        self.load_killers(context);
+                        self.stage = Stage::Killers;
+                        continue;
+                    };
+
+                    if scoring_move.bit_move == self.tt_move {
+                        continue;
+                    }
+
+                    // NOTE: Losing captures are delayed until all quiet moves have been searched
+                    #[cfg(feature = "see")]
+                    if EvalMove::see(self.position, scoring_move.bit_move) < Score::ZERO {
+                        self.bad_captures.add(scoring_move);
+                        continue;
+                    }
+
+                    return Some(scoring_move);
+                },
+                Stage::Killers => {
+                    let Some(&killer) = self.killers.get(self.killer_index) else {
+                        self.stage = Stage::GenerateQuiets;
+                        continue;
+                    };
+
+                    // NOTE: Killers that aren't quiet moves in this position are cleared, so the
+                    // quiet stage doesn't skip them if they're generated after all
+                    if self.is_killer_valid(killer) {
+                        self.killer_index += 1;
+                        return Some(ScoringMove::new(killer, Score::ZERO));
+                    }
+
+                    self.killers[self.killer_index] = BitMove::EMPTY;
+                    self.killer_index += 1;
+                },
+                Stage::GenerateQuiets => {
+                    self.quiets = MoveGeneration::generate_quiets::<ScoringMove, PseudoLegal>(self.position);
+                    self.index = 0;
+                    self.add_quiet_heuristics(context);
+                    self.stage = Stage::Quiets;
+                },
+                Stage::Quiets => {
+                    let Some(scoring_move) = Self::select_best(&mut self.quiets, &mut self.index) else {
+                        self.index = 0;
+                        self.stage = Stage::BadCaptures;
+                        continue;
+                    };
+
+                    if scoring_move.bit_move == self.tt_move || self.killers.contains(&scoring_move.bit_move) || scoring_move.bit_move.flag_option() == Some(MoveFlag::PromoQ) {
+                        continue;
+                    }
+
+                    return Some(scoring_move);
+                },
+                Stage::BadCaptures => {
+                    if self.index >= self.bad_captures.len() {
+                        self.stage = Stage::Done;
+                        continue;
+                    }
+
+                    self.index += 1;
+                    return Some(self.bad_captures[self.index - 1]);
+                },
+                Stage::Done => return None,
+            }
+        }
+    }
+
+    // NOTE: The transposition table verifies the full zobrist key, so the move belongs to this
+    // position unless the key collides, in which case it could be any move
+    #[inline(always)]
+    fn is_tt_move_valid(&self) -> bool {
+        MoveGeneration::is_pseudo_legal(self.position, self.tt_move)
+    }
+
+    // NOTE: Killers are only searched if they're quiet moves in this position, since they
+    // stem from other positions at the same ply, and queen promotions come with the captures
+    #[inline(always)]
+    fn is_killer_valid(&self, killer: BitMove) -> bool {
+        !killer.is_capture(self.position) &&
+        killer.flag_option().is_none_or(|flag| !flag.is_en_passant() && flag != MoveFlag::PromoQ) &&
+        MoveGeneration::is_pseudo_legal(self.position, killer)
+    }
+
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn load_killers(&mut self, context: &SearchContext) {
+        #[cfg(feature = "killer_heuristic")]
+        {
+            let killers = [context.killer_moves.get_primary(self.position.ply), context.killer_moves.get_secondary(self.position.ply)];
+            for (killer, killer_option) in self.killers.iter_mut().zip(killers) {
+                *killer = killer_option.filter(|&killer| killer != self.tt_move).unwrap_or(BitMove::EMPTY);
+            }
+
+            if self.killers[1] == self.killers[0] {
+                self.killers[1] = BitMove::EMPTY;
+            }
+        }
+    }
+
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn add_quiet_heuristics(&mut self, context: &SearchContext) {
+        #[cfg(feature = "history_heuristic")]
+        for scoring_move in self.quiets.iter_mut() {
+            scoring_move.score += context.history_heuristic.get(self.position.side, scoring_move.bit_move.source(), scoring_move.bit_move.target());
+        }
+    }
+
+    // NOTE: Selection sort is done lazily, since most nodes cut off after only a few moves
+    #[inline(always)]
+    fn select_best(moves: &mut MoveList<ScoringMove>, index: &mut usize) -> Option<ScoringMove> {
+        if *index >= moves.len() {
+            return None;
+        }
+
+        let mut best_index = *index;
+        for current_index in *index + 1..moves.len() {
+            if moves[current_index].score > moves[best_index].score {
+                best_index = current_index;
+            }
+        }
+
+        let best_move = moves[best_index];
+        moves[best_index] = moves[*index];
+        moves[*index] = best_move;
+        *index += 1;
+        Some(best_move)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::FenString;
+
+    use super::*;
+
+    fn picked_moves(position: &Position, tt_move: BitMove, context: &SearchContext) -> Vec<BitMove> {
+        let mut move_picker = MovePicker::new(position, tt_move, position.in_check(position.side));
+        let mut moves = Vec::new();
+        while let Some(scoring_move) = move_picker.next(context) {
+            moves.push(scoring_move.bit_move);
+        }
+        moves
+    }
+
+    #[test]
+    fn picks_every_pseudo_legal_move_exactly_once() {
+        let context = SearchContext::default();
+        for fen in [FenString::startpos(), FenString::kiwipete(), FenString::tricky()] {
+            let position: Position = fen.parse().unwrap();
+            // NOTE: Evasions are all pseudo-legal moves for positions not in check
+            let pseudo_legal_moves: HashSet<BitMove> = MoveGeneration::generate_evasions::<BitMove, PseudoLegal>(&position).iter().copied().collect();
+            let tt_move = *pseudo_legal_moves.iter().next().unwrap();
+
+            for tt_move in [BitMove::EMPTY, tt_move] {
+                let moves = picked_moves(&position, tt_move, &context);
+                assert_eq!(moves.len(), pseudo_legal_moves.len());
+                assert_eq!(moves.iter().copied().collect::<HashSet<BitMove>>(), pseudo_legal_moves);
+            }
+        }
+    }
+
+    #[test]
+    fn picks_tt_move_first_then_good_captures_quiets_and_bad_captures() {
+        let context = SearchContext::default();
+        let position: Position = FenString::kiwipete().parse().unwrap();
+        let tt_move = MoveGeneration::generate_quiets::<BitMove, PseudoLegal>(&position).first();
+        let moves = picked_moves(&position, tt_move, &context);
+        assert_eq!(moves[0], tt_move);
+
+        let stages: Vec<u8> = moves[1..]
+            .iter()
+            .map(|&bit_move| match bit_move.is_capture(&position) {
+                false => 1,
+                true if cfg!(feature = "see") && EvalMove::see(&position, bit_move) < Score::ZERO => 2,
+                true => 0,
+            })
+            .collect();
+        assert!(stages.is_sorted());
+    }
+
+    #[test]
+    #[cfg(feature = "killer_heuristic")]
+    fn picks_valid_killers_before_other_quiets() {
+        let mut context = SearchContext::default();
+        let position: Position = FenString::kiwipete().parse().unwrap();
+        let parse_move = |position: &Position, move_string: &str| {
+            crate::Uci::parse_move_string(&MoveGeneration::generate_moves::<BitMove, PseudoLegal>(position), move_string).unwrap()
+        };
+        let killer = parse_move(&position, "a2a3");
+        context.killer_moves.update(killer, position.ply);
+        context.killer_moves.update(parse_move(&Position::starting_position(), "g1f3"), position.ply);
+
+        let moves = picked_moves(&position, BitMove::EMPTY, &context);
+        let pseudo_legal_moves: HashSet<BitMove> = MoveGeneration::generate_moves::<BitMove, PseudoLegal>(&position).iter().copied().collect();
+        assert_eq!(moves.len(), pseudo_legal_moves.len());
+        assert_eq!(moves.iter().copied().collect::<HashSet<BitMove>>(), pseudo_legal_moves);
+
+        let killer_index = moves.iter().position(|&bit_move| bit_move == killer).unwrap();
+        assert!(moves[..killer_index].iter().all(|bit_move| bit_move.is_capture(&position)));
+        assert!(!moves[killer_index + 1].is_capture(&position));
+    }
+
+    #[test]
+    #[cfg(feature = "killer_heuristic")]
+    fn picks_quiet_queen_promotions_before_killers() {
+        let mut context = SearchContext::default();
+        let position: Position = FenString::from("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").parse().unwrap();
+        let pseudo_legal_moves = MoveGeneration::generate_moves::<BitMove, PseudoLegal>(&position);
+        let queen_promotion = crate::Uci::parse_move_string(&pseudo_legal_moves, "b7b8q").unwrap();
+        let killer = crate::Uci::parse_move_string(&pseudo_legal_moves, "e1d1").unwrap();
+        context.killer_moves.update(killer, position.ply);
+        context.killer_moves.update(queen_promotion, position.ply);
+
+        let moves = picked_moves(&position, BitMove::EMPTY, &context);
+        assert_eq!(moves.len(), pseudo_legal_moves.len());
+        assert_eq!(moves[..2], [queen_promotion, killer]);
+    }
+
+    #[test]
+    fn picks_only_evasions_in_check() {
+        let context = SearchContext::default();
+        let position: Position = FenString::from("4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1").parse().unwrap();
+        let evasions: HashSet<BitMove> = MoveGeneration::generate_evasions::<BitMove, PseudoLegal>(&position).iter().copied().collect();
+        let moves = picked_moves(&position, BitMove::EMPTY, &context);
+        assert_eq!(moves.len(), evasions.len());
+        assert_eq!(moves.into_iter().collect::<HashSet<BitMove>>(), evasions);
+    }
+}
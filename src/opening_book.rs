@@ -1,9 +1,10 @@
 use std::time::Duration;
 use serde::Deserialize;
 
-use crate::{BitMove, Color, FenString, Legal, MoveGeneration, Position, Uci};
+use crate::{BitMove, Color, FenString, Legal, MoveGeneration, OpeningBookError, PolyglotBook, Position, StatsBook, Uci, STATS_BOOK_EXTENSION};
 
-const NUM_GAMES_THRESHOLD: u32 = 1_000;
+const LICHESS_NUM_GAMES_THRESHOLD: u32 = 1_000;
+const STATS_BOOK_NUM_GAMES_THRESHOLD: u32 = 10;
 const WINRATE_THRESHOLD: f32 = 0.35;
 const OPENING_BOOK_TIMEOUT_MS: u64 = 500;
 
@@ -15,15 +16,27 @@ struct LichessOpeningStats {
 #[derive(Deserialize)]
 struct LichessMoveStats {
     uci: String,
-    white: u32,
-    draws: u32,
-    black: u32,
+    #[serde(flatten)]
+    stats: MoveStats,
 }
 
-impl LichessMoveStats {
+// The results of the games in which a move was played from a position
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub(crate) struct MoveStats {
+    pub(crate) white: u32,
+    pub(crate) draws: u32,
+    pub(crate) black: u32,
+}
+
+impl MoveStats {
+    #[inline(always)]
+    pub(crate) fn num_games(&self) -> u32 {
+        self.white + self.draws + self.black
+    }
+
     #[inline(always)]
-    fn has_enough_games(&self) -> bool {
-        self.white + self.draws + self.black >= NUM_GAMES_THRESHOLD
+    fn has_enough_games(&self, num_games_threshold: u32) -> bool {
+        self.num_games() >= num_games_threshold
     }
     
     #[inline(always)]
@@ -42,8 +55,8 @@ impl LichessMoveStats {
     }
 
     #[inline(always)]
-    fn is_candidate(&self, side: Color) -> bool {
-        self.has_enough_games() && self.has_acceptable_winrate(side)
+    fn is_candidate(&self, side: Color, num_games_threshold: u32) -> bool {
+        self.has_enough_games(num_games_threshold) && self.has_acceptable_winrate(side)
     }
 }
 
@@ -54,7 +67,7 @@ impl LichessOpeningStats {
         self.moves
             .iter()
             .filter_map(|opening_move| {
-                if opening_move.is_candidate(position.side) {
+                if opening_move.stats.is_candidate(position.side, LICHESS_NUM_GAMES_THRESHOLD) {
                     Uci::parse_move_string(&legal_moves, &opening_move.uci).ok()
                 } else {
                     None
@@ -64,36 +77,86 @@ impl LichessOpeningStats {
     }
 }
 
+// NOTE: A local book never falls back to the Lichess backend, so searches with it are
+// reproducible and don't depend on the network
+enum OpeningBookSource {
+    Lichess(ureq::Agent),
+    Polyglot(PolyglotBook),
+    Stats(StatsBook),
+}
+
 pub(crate) struct OpeningBook {
-    agent: ureq::Agent
+    source: OpeningBookSource,
 }
 
 impl Default for OpeningBook {
     fn default() -> Self {
         Self {
-            agent: ureq::Agent::config_builder()
-                .timeout_global(Some(Duration::from_millis(OPENING_BOOK_TIMEOUT_MS)))
-                .build()
-                .into()
+            source: OpeningBookSource::Lichess(
+                ureq::Agent::config_builder()
+                    .timeout_global(Some(Duration::from_millis(OPENING_BOOK_TIMEOUT_MS)))
+                    .build()
+                    .into()
+            ),
         }
     }
 }
 
 impl OpeningBook {
-    fn get_lichess_opening_stats(&self, position: &Position) -> Result<LichessOpeningStats, ureq::Error> {
+    // NOTE: Books built from PGN files by the book builder are recognized by their extension,
+    // while all other files are read as Polyglot books
+    pub(crate) fn from_file(path: &str) -> Result<OpeningBook, OpeningBookError> {
+        let source = match path.ends_with(STATS_BOOK_EXTENSION) {
+            true => OpeningBookSource::Stats(StatsBook::from_file(path)?),
+            false => OpeningBookSource::Polyglot(PolyglotBook::from_file(path)?),
+        };
+
+        Ok(Self { source })
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_local(&self) -> bool {
+        !matches!(self.source, OpeningBookSource::Lichess(_))
+    }
+
+    fn get_lichess_opening_stats(agent: &ureq::Agent, position: &Position) -> Result<LichessOpeningStats, ureq::Error> {
         let fen_string = FenString::from(position);
         let fen_with_replaced_spaces = fen_string.to_string().replace(" ", "_");
         let uri = &format!("https://explorer.lichess.ovh/masters?fen={fen_with_replaced_spaces}");
-        let resp = self.agent.get(uri).call();
+        let resp = agent.get(uri).call();
         let body = resp?.body_mut().read_to_string()?;
         let lichess_opening_stats: LichessOpeningStats = serde_json::from_str(&body)
             .map_err(ureq::Error::Json)?;
         Ok(lichess_opening_stats)
     }
 
-    pub(crate) fn get_move(&self, position: &Position) -> Option<BitMove> {
-        let lichess_opening_stats = self.get_lichess_opening_stats(position).ok()?;
-        let opening_move_contenders = lichess_opening_stats.get_opening_move_contenders(position);
-        rand::seq::IteratorRandom::choose(opening_move_contenders.iter(), &mut rand::rng()).copied()
+    // NOTE: The Lichess backend has no weights, so its best move is the most played candidate
+    pub(crate) fn get_move(&self, position: &Position, best_move_only: bool) -> Option<BitMove> {
+        match &self.source {
+            OpeningBookSource::Lichess(agent) => {
+                let lichess_opening_stats = Self::get_lichess_opening_stats(agent, position).ok()?;
+                let opening_move_contenders = lichess_opening_stats.get_opening_move_contenders(position);
+                match best_move_only {
+                    true => opening_move_contenders.first().copied(),
+                    false => rand::seq::IteratorRandom::choose(opening_move_contenders.iter(), &mut rand::rng()).copied(),
+                }
+            },
+            OpeningBookSource::Polyglot(polyglot_book) => match best_move_only {
+                true => polyglot_book.get_best_move(position),
+                false => polyglot_book.get_weighted_move(position),
+            },
+            OpeningBookSource::Stats(stats_book) => {
+                let opening_move_contenders: Vec<(BitMove, MoveStats)> = stats_book
+                    .get_move_stats(position)
+                    .into_iter()
+                    .filter(|(_, move_stats)| move_stats.is_candidate(position.side, STATS_BOOK_NUM_GAMES_THRESHOLD))
+                    .collect();
+
+                match best_move_only {
+                    true => opening_move_contenders.iter().max_by_key(|(_, move_stats)| move_stats.num_games()),
+                    false => rand::seq::IteratorRandom::choose(opening_move_contenders.iter(), &mut rand::rng()),
+                }.map(|&(bit_move, _)| bit_move)
+            },
+        }
     }
 }
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::{BitMove, FenString, MoveGeneration, Position, PseudoLegal, Timer};
+use crate::{BitMove, FenString, MoveGeneration, MoveList, Position, Timer};
 
 use std::sync::Arc;
 
@@ -48,13 +48,36 @@ impl Perft {
         return Self::perft_test_single_thread_clone(position, depth, print_result);
 
         #[cfg(feature = "parallel_perft")]
-        if crate::GlobalThreadPool::should_parallelize() {
-            return Self::perft_test_parallelize(position, depth, print_result);
+        if crate::ThreadPool::should_parallelize_current() {
+            Self::perft_test_parallelize(position, depth, print_result)
         } else {
-            return Self::perft_test_single_thread_clone(position, depth, print_result);
+            Self::perft_test_single_thread_clone(position, depth, print_result)
         }
     }
 
+    // NOTE: The legal generator only emits legal moves, so they're made without checking legality
+    #[inline(always)]
+    fn generate_moves(position: &Position) -> MoveList<BitMove> {
+        #[cfg(feature = "legal_movegen")]
+        return MoveGeneration::generate_legal_moves(position);
+
+        #[cfg(not(feature = "legal_movegen"))]
+        return MoveGeneration::generate_moves::<BitMove, crate::PseudoLegal>(position);
+    }
+
+    #[inline(always)]
+    fn apply_move(position: &mut Position, bit_move: BitMove) -> bool {
+        #[cfg(feature = "legal_movegen")]
+        {
+            position.make_move(bit_move);
+            position.ply += 1;
+            true
+        }
+
+        #[cfg(not(feature = "legal_movegen"))]
+        position.apply_pseudo_legal_move(bit_move)
+    }
+
     #[inline(always)]
     fn perft_test_single_thread_undo_move(position: &Position, depth: u16, print_result: bool) -> PerftResult {
         let mut current_nodes = 0_u64;
@@ -68,9 +91,9 @@ impl Perft {
         #[cfg(feature = "revert_undo")]
         let old_castling_rights = position.castling_rights;
         
-        for bit_move in MoveGeneration::generate_moves::<BitMove, PseudoLegal>(position) {
+        for bit_move in Self::generate_moves(position) {
             position_copy.make_move(bit_move);
-            if !position_copy.in_check(position_copy.side.opposite()) {
+            if cfg!(feature = "legal_movegen") || !position_copy.in_check(position_copy.side.opposite()) {
                 current_nodes += Self::perft_driver_single_thread_undo_move(&position_copy, depth - 1);
 
                 if print_result {
@@ -106,9 +129,9 @@ impl Perft {
 
         if print_result { println!("\n  Performance Test\n"); }
 
-        for bit_move in MoveGeneration::generate_moves::<BitMove, PseudoLegal>(position) {
+        for bit_move in Self::generate_moves(position) {
             let mut new_position = position.clone();
-            if new_position.apply_pseudo_legal_move(bit_move) {
+            if Self::apply_move(&mut new_position, bit_move) {
                 current_nodes += Self::perft_driver_single_thread_clone(&new_position, depth - 1);
                 
                 if print_result {
@@ -146,11 +169,11 @@ impl Perft {
         let position_arc = Arc::new(position.clone());
 
         // Computes nodes reached in parallel
-        let cumulative_nodes = MoveGeneration::generate_moves::<BitMove, PseudoLegal>(position)
+        let cumulative_nodes = Self::generate_moves(position)
             .par_iter()
             .map(|&bit_move| {
                 let mut new_position = (*position_arc).clone();
-                if new_position.apply_pseudo_legal_move(bit_move) {
+                if Self::apply_move(&mut new_position, bit_move) {
                     let nodes = Self::perft_driver_parallelize(Arc::new(new_position), depth - 1);
                     if print_result {
                         println!("  Move: {:<5} Nodes: {}", bit_move.to_uci_string(), nodes);
@@ -188,9 +211,9 @@ impl Perft {
             #[cfg(feature = "revert_undo")]
             let old_castling_rights = position.castling_rights;
             
-            for bit_move in MoveGeneration::generate_moves::<BitMove, PseudoLegal>(position) {
+            for bit_move in Self::generate_moves(position) {
                 position_copy.make_move(bit_move);
-                if !position_copy.in_check(position_copy.side.opposite()) {
+                if cfg!(feature = "legal_movegen") || !position_copy.in_check(position_copy.side.opposite()) {
                     nodes += Self::perft_driver_single_thread_undo_move(&position_copy, depth - 1);
                 }
 
@@ -206,11 +229,11 @@ impl Perft {
         if depth == 0 {
             1
         } else {
-            MoveGeneration::generate_moves::<BitMove, PseudoLegal>(position)
+            Self::generate_moves(position)
                 .iter()
                 .map(|&bit_move| {
                     let mut new_position = position.clone();
-                    if new_position.apply_pseudo_legal_move(bit_move) {
+                    if Self::apply_move(&mut new_position, bit_move) {
                         Self::perft_driver_single_thread_clone(&new_position, depth - 1)
                     } else {
                         0
@@ -227,11 +250,11 @@ impl Perft {
             1
         } else if depth <= 2 {
             // Recursively counts nodes sequentially
-            MoveGeneration::generate_moves::<BitMove, PseudoLegal>(&position_arc)
+            Self::generate_moves(&position_arc)
                 .iter()
                 .map(|&bit_move| {
                     let mut new_position = (*position_arc).clone();
-                    if new_position.apply_pseudo_legal_move(bit_move) {
+                    if Self::apply_move(&mut new_position, bit_move) {
                         Self::perft_driver_parallelize(Arc::new(new_position), depth - 1)
                     } else {
                         0
@@ -240,11 +263,11 @@ impl Perft {
                 .sum()
         } else {
             // Recursively counts nodes in parallel
-            MoveGeneration::generate_moves::<BitMove, PseudoLegal>(&position_arc)
+            Self::generate_moves(&position_arc)
                 .par_iter()
                 .map(|&bit_move| {
                     let mut new_position = (*position_arc).clone();
-                    if new_position.apply_pseudo_legal_move(bit_move) {
+                    if Self::apply_move(&mut new_position, bit_move) {
                         Self::perft_driver_parallelize(Arc::new(new_position), depth - 1)
                     } else {
                         0
@@ -388,10 +411,126 @@ impl Perft {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
+    use crate::Legal;
+
     use super::*;
 
     #[test]
     fn short_perft_tests_are_correct() {
         Perft::short_perft_tests();
     }
+
+    // Counts nodes with the evasion generator in check, while asserting that it generates exactly
+    // the legal moves
+    fn perft_driver_evasions(position: &Position, depth: u16) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        if !position.in_check(position.side) {
+            return MoveGeneration::generate_moves::<BitMove, Legal>(position)
+                .iter()
+                .map(|&bit_move| {
+                    let mut new_position = position.clone();
+                    new_position.make_move(bit_move);
+                    perft_driver_evasions(&new_position, depth - 1)
+                })
+                .sum();
+        }
+
+        let evasions = MoveGeneration::generate_evasions::<BitMove, Legal>(position);
+        let legal_moves: HashSet<BitMove> = MoveGeneration::generate_moves::<BitMove, Legal>(position).iter().copied().collect();
+        assert_eq!(evasions.len(), legal_moves.len(), "{position}");
+        assert_eq!(evasions.iter().copied().collect::<HashSet<BitMove>>(), legal_moves, "{position}");
+
+        evasions
+            .iter()
+            .map(|&bit_move| {
+                let mut new_position = position.clone();
+                new_position.make_move(bit_move);
+                perft_driver_evasions(&new_position, depth - 1)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn evasion_perft_matches_legal_perft() {
+        for (fen, depth, target_nodes) in [
+            (FenString::kiwipete(), 3, 97_862),
+            (FenString::rook(), 4, 43_238),
+            (FenString::tricky(), 3, 9_467),
+            (FenString::tricky2(), 3, 62_379),
+        ] {
+            let position = fen.parse().unwrap();
+            assert_eq!(perft_driver_evasions(&position, depth), target_nodes, "{fen}");
+        }
+    }
+
+    #[test]
+    fn evasions_handle_double_checks_and_en_passant() {
+        for (fen, move_count) in [
+            ("4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1", 2),
+            ("8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1", 9),
+            ("8/8/8/8/k2Pp2Q/8/8/4K3 b - d3 0 1", 6),
+        ] {
+            let position: Position = FenString::from(fen).parse().unwrap();
+            let evasions = MoveGeneration::generate_evasions::<BitMove, Legal>(&position);
+            assert_eq!(evasions.len(), move_count, "{fen}");
+            assert_eq!(evasions.len(), MoveGeneration::generate_moves::<BitMove, Legal>(&position).len(), "{fen}");
+        }
+    }
+
+    // Counts nodes with the legal generator, while asserting that it generates exactly the
+    // moves that pass the make-move legality check
+    fn perft_driver_legal(position: &Position, depth: u16) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = MoveGeneration::generate_legal_moves::<BitMove>(position);
+        let legal_moves: HashSet<BitMove> = MoveGeneration::generate_moves::<BitMove, Legal>(position).iter().copied().collect();
+        assert_eq!(moves.len(), legal_moves.len(), "{position}");
+        assert_eq!(moves.iter().copied().collect::<HashSet<BitMove>>(), legal_moves, "{position}");
+
+        moves
+            .iter()
+            .map(|&bit_move| {
+                let mut new_position = position.clone();
+                new_position.make_move(bit_move);
+                perft_driver_legal(&new_position, depth - 1)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn legal_perft_matches_pseudo_legal_perft() {
+        for (fen, depth, target_nodes) in [
+            (FenString::startpos(), 3, 8_902),
+            (FenString::kiwipete(), 3, 97_862),
+            (FenString::rook(), 4, 43_238),
+            (FenString::tricky(), 3, 9_467),
+            (FenString::tricky2(), 3, 62_379),
+        ] {
+            let position = fen.parse().unwrap();
+            assert_eq!(perft_driver_legal(&position, depth), target_nodes, "{fen}");
+        }
+    }
+
+    #[test]
+    fn legal_moves_handle_pins_and_en_passant() {
+        for (fen, move_count) in [
+            ("4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1", 2),
+            ("8/8/8/8/k2Pp2Q/8/8/4K3 b - d3 0 1", 6),
+            ("8/8/8/KPp4r/8/8/8/4k3 w - c6 0 1", 4),
+            ("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1", 4),
+            ("4k3/8/8/7b/8/8/4P3/3K4 w - - 0 1", 4),
+        ] {
+            let position: Position = FenString::from(fen).parse().unwrap();
+            let moves = MoveGeneration::generate_legal_moves::<BitMove>(&position);
+            assert_eq!(moves.len(), move_count, "{fen}");
+            assert_eq!(moves.len(), MoveGeneration::generate_moves::<BitMove, Legal>(&position).len(), "{fen}");
+        }
+    }
 }
@@ -0,0 +1,475 @@
+use std::{collections::VecDeque, fmt::Display, fs::File, io::{BufRead, BufReader, Lines, Write}};
+
+use crate::{BitMove, Color, FenString, PgnParseError, Position};
+
+const RESULT_STRINGS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+// NOTE: The result completes the seven tag roster, but it's always taken from the game itself
+const SEVEN_TAG_ROSTER: [(&str, &str); 6] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+];
+const MAX_LINE_LENGTH: usize = 80;
+
+// A game as it's stored in PGN, which is its tags, the position it starts from and the moves of
+// its main line: https://www.chessprogramming.org/Portable_Game_Notation
+#[derive(Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub start_position: Position,
+    pub moves: Vec<BitMove>,
+    pub result: String,
+}
+
+impl Default for PgnGame {
+    fn default() -> Self {
+        Self::new(Position::starting_position(), Vec::new(), "*")
+    }
+}
+
+impl PgnGame {
+    pub fn new(start_position: Position, moves: Vec<BitMove>, result: &str) -> PgnGame {
+        PgnGame { tags: Vec::new(), start_position, moves, result: result.to_string() }
+    }
+
+    pub fn get_tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_name, _)| tag_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag_name, _)| tag_name == name) {
+            Some((_, tag_value)) => *tag_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn get_final_position(&self) -> Position {
+        let mut position = self.start_position.clone();
+        for &bit_move in &self.moves {
+            position.make_move(bit_move);
+        }
+        position
+    }
+
+    // NOTE: Games are separated by an empty line, so several games can be written to one file
+    pub fn write<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writeln!(writer, "{self}")
+    }
+
+    // NOTE: The seven tag roster comes first and the setup tags are derived from the start
+    // position, while the remaining tags are written in their original order
+    fn get_export_tags(&self) -> Vec<(&str, String)> {
+        let mut tags: Vec<(&str, String)> = SEVEN_TAG_ROSTER
+            .iter()
+            .map(|&(name, default_value)| (name, self.get_tag(name).unwrap_or(default_value).to_string()))
+            .collect();
+        tags.push(("Result", self.result.clone()));
+
+        let fen_string = FenString::from(&self.start_position).to_string();
+        if fen_string != FenString::from(&Position::starting_position()).to_string() {
+            tags.push(("SetUp", String::from("1")));
+            tags.push(("FEN", fen_string));
+        }
+
+        for (name, value) in &self.tags {
+            if !tags.iter().any(|(tag_name, _)| tag_name == name) && name != "SetUp" && name != "FEN" {
+                tags.push((name, value.clone()));
+            }
+        }
+
+        tags
+    }
+
+    fn get_movetext_tokens(&self) -> Vec<String> {
+        let mut position = self.start_position.clone();
+        let mut tokens = Vec::new();
+
+        for (index, &bit_move) in self.moves.iter().enumerate() {
+            match position.side {
+                Color::White => tokens.push(format!("{}.", position.fullmove_number)),
+                Color::Black if index == 0 => tokens.push(format!("{}...", position.fullmove_number)),
+                Color::Black => (),
+            }
+
+            tokens.push(bit_move.to_san_string(&position));
+            position.make_move(bit_move);
+        }
+
+        tokens.push(self.result.clone());
+        tokens
+    }
+}
+
+// NOTE: Movetext lines are wrapped, since PGN lines shouldn't be longer than 80 characters
+impl Display for PgnGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, value) in self.get_export_tags() {
+            writeln!(f, "[{name} \"{}\"]", value.replace('\\', "\\\\").replace('"', "\\\""))?;
+        }
+        writeln!(f)?;
+
+        let mut line_length = 0;
+        for token in self.get_movetext_tokens() {
+            if line_length > 0 && line_length + 1 + token.len() > MAX_LINE_LENGTH {
+                writeln!(f)?;
+                line_length = 0;
+            } else if line_length > 0 {
+                write!(f, " ")?;
+                line_length += 1;
+            }
+
+            write!(f, "{token}")?;
+            line_length += token.len();
+        }
+
+        writeln!(f)
+    }
+}
+
+struct PartialPgnGame {
+    pgn_game: PgnGame,
+    position: Position,
+    error_option: Option<PgnParseError>,
+}
+
+impl Default for PartialPgnGame {
+    fn default() -> Self {
+        Self {
+            pgn_game: PgnGame::default(),
+            position: Position::starting_position(),
+            error_option: None,
+        }
+    }
+}
+
+impl PartialPgnGame {
+    // NOTE: Only the first error of a game is kept, since later ones are usually caused by it
+    #[inline(always)]
+    fn set_error(&mut self, error: PgnParseError) {
+        self.error_option.get_or_insert(error);
+    }
+}
+
+// Reads the games of a PGN file one line at a time, so files of any size can be read. Comments,
+// NAGs and variations are skipped, and a game that can't be parsed results in an error with the
+// line number of the cause, after which reading continues with the next game.
+pub struct PgnReader<R: BufRead> {
+    lines: Lines<R>,
+    line_number: usize,
+    partial_game_option: Option<PartialPgnGame>,
+    finished_games: VecDeque<Result<PgnGame, PgnParseError>>,
+    in_comment: bool,
+    variation_depth: usize,
+}
+
+impl PgnReader<BufReader<File>> {
+    pub fn from_file(path: &str) -> Result<PgnReader<BufReader<File>>, PgnParseError> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(reader: R) -> PgnReader<R> {
+        PgnReader {
+            lines: reader.lines(),
+            line_number: 0,
+            partial_game_option: None,
+            finished_games: VecDeque::new(),
+            in_comment: false,
+            variation_depth: 0,
+        }
+    }
+
+    fn read_line(&mut self, line: &str) {
+        let trimmed_line = line.trim();
+        if !self.in_comment && self.variation_depth == 0 {
+            if trimmed_line.starts_with('%') {
+                return;
+            }
+
+            if trimmed_line.starts_with('[') {
+                self.read_tag(trimmed_line);
+                return;
+            }
+        }
+
+        let mut token = String::new();
+        for c in line.chars() {
+            if self.in_comment {
+                self.in_comment = c != '}';
+                continue;
+            }
+
+            if c.is_whitespace() || "{};()".contains(c) {
+                self.read_token(&token);
+                token.clear();
+            }
+
+            match c {
+                '{' => self.in_comment = true,
+                ';' => return,
+                '(' => self.variation_depth += 1,
+                '}' => self.set_token_error(c),
+                ')' if self.variation_depth == 0 => self.set_token_error(c),
+                ')' => self.variation_depth -= 1,
+                _ if c.is_whitespace() => (),
+                _ => token.push(c),
+            }
+        }
+
+        self.read_token(&token);
+    }
+
+    // NOTE: Tags after the movetext of a game mean that its result is missing
+    fn read_tag(&mut self, line: &str) {
+        let line_number = self.line_number;
+        if self.partial_game_option.as_ref().is_some_and(|partial_game| !partial_game.pgn_game.moves.is_empty()) {
+            self.partial_game_option = None;
+            self.finished_games.push_back(Err(PgnParseError::MissingResult(line_number)));
+        }
+
+        let tag_option = line
+            .strip_prefix('[')
+            .and_then(|tag| tag.strip_suffix(']'))
+            .and_then(|tag| tag.trim().split_once(char::is_whitespace))
+            .and_then(|(name, value)| Some((name, value.trim().strip_prefix('"')?.strip_suffix('"')?)));
+
+        let partial_game = self.get_partial_game();
+        let Some((name, value)) = tag_option else {
+            partial_game.set_error(PgnParseError::Tag(line_number, line.to_string()));
+            return;
+        };
+
+        let value = value.replace("\\\"", "\"").replace("\\\\", "\\");
+        if name == "FEN" {
+            match FenString::from(value.as_str()).parse() {
+                Ok(position) => {
+                    partial_game.position = position;
+                    partial_game.pgn_game.start_position = partial_game.position.clone();
+                },
+                Err(error) => partial_game.set_error(PgnParseError::Fen(line_number, error)),
+            }
+        }
+
+        partial_game.pgn_game.tags.push((name.to_string(), value));
+    }
+
+    // NOTE: Move numbers can be attached to the moves they number, like in 1.e4
+    fn read_token(&mut self, token: &str) {
+        if token.is_empty() || self.variation_depth > 0 {
+            return;
+        }
+
+        if RESULT_STRINGS.contains(&token) {
+            let partial_game = self.partial_game_option.take().unwrap_or_default();
+            self.finished_games.push_back(match partial_game.error_option {
+                Some(error) => Err(error),
+                None => Ok(PgnGame { result: token.to_string(), ..partial_game.pgn_game }),
+            });
+            return;
+        }
+
+        let san = token.rsplit('.').next().unwrap_or_default();
+        if san.is_empty() || san.starts_with('$') || san.chars().all(|c| c == '!' || c == '?') {
+            return;
+        }
+
+        let line_number = self.line_number;
+        let partial_game = self.get_partial_game();
+        if partial_game.error_option.is_some() {
+            return;
+        }
+
+        match partial_game.position.parse_san(san) {
+            Ok(bit_move) => {
+                partial_game.position.make_move(bit_move);
+                partial_game.pgn_game.moves.push(bit_move);
+            },
+            Err(error) => partial_game.set_error(PgnParseError::Move(line_number, error)),
+        }
+    }
+
+    #[inline(always)]
+    fn set_token_error(&mut self, c: char) {
+        let line_number = self.line_number;
+        self.get_partial_game().set_error(PgnParseError::Token(line_number, c.to_string()));
+    }
+
+    #[inline(always)]
+    fn get_partial_game(&mut self) -> &mut PartialPgnGame {
+        self.partial_game_option.get_or_insert_with(Default::default)
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(game) = self.finished_games.pop_front() {
+                return Some(game);
+            }
+
+            match self.lines.next() {
+                Some(Ok(line)) => {
+                    self.line_number += 1;
+                    self.read_line(&line);
+                },
+                Some(Err(error)) => return Some(Err(error.into())),
+                None => {
+                    let line_number = self.line_number;
+                    return self.partial_game_option.take().map(|_| Err(PgnParseError::MissingResult(line_number)));
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SanParseError;
+
+    use super::*;
+
+    const PGN: &str = r#"[Event "Test \"quoted\""]
+[Site "?"]
+[Result "1-0"]
+[ECO "C60"]
+
+1.e4 {The king's pawn,
+spanning two lines} e5 2. Nf3 (2. f4 exf4 (2... d5) 3. Nf3) Nc6 $1 3. Bb5!? a6 ; Morphy
+4. Ba4 1-0
+
+[Event "Setup"]
+[SetUp "1"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 b - - 0 10"]
+[Result "1/2-1/2"]
+
+10... Kd7 11. e4 1/2-1/2
+"#;
+
+    const OPENING_PGN: &str = r#"[Event "Wch U16"]
+[Site "Wattignies"]
+[Date "1976.??.??"]
+[Round "?"]
+[White "Chandler, Murray G"]
+[Black "Kasparov, Garry"]
+[Result "1-0"]
+[ECO "B22"]
+[PlyCount "82"]
+[EventDate "1976.??.??"]
+
+1.e4 c5 2.c3 Nf6 3.e5 Nd5 4.d4 Nc6 5.Nf3 cxd4 6.cxd4 e6 7.a3 d6 8.Bd3 Qa5+
+9.Bd2 Qb6 10.Nc3 Nxc3 11.Bxc3 dxe5 12.dxe5 Be7 13.O-O Bd7 14.Nd2 Qc7 15.Qg4 O-O-O
+16.Rfc1 Kb8 17.Qc4 Rc8 18.b4 f6 19.Nf3 Qb6 20.Qe4 f5 21.Qe1 a6 22.Rab1 g5
+23.Nd2 Nd4 24.Qe3 Rxc3 25.Rxc3 f4 26.Qe1 g4 27.Ne4 Bc6 28.Nc5 Ka7 29.a4 Bf3
+30.a5 Qd8 31.Bc4 Bxc5 32.bxc5 Qh4 33.gxf3 gxf3 34.Kh1 Rg8 35.Qe4 Rg7 36.Qxd4 Qg5
+37.c6+ Kb8 38.c7+ Rxc7 39.Rg1 Qh5 40.Rg8+ Rc8 41.Qd6+ Ka7  1-0
+"#;
+
+    fn read_games(pgn: &str) -> Vec<Result<PgnGame, PgnParseError>> {
+        PgnReader::new(pgn.as_bytes()).collect()
+    }
+
+    fn move_strings(pgn_game: &PgnGame) -> Vec<String> {
+        pgn_game.moves.iter().map(|bit_move| bit_move.to_uci_string()).collect()
+    }
+
+    #[test]
+    fn pgn_games_are_read() {
+        let games = read_games(PGN);
+        assert_eq!(games.len(), 2);
+
+        let game = games[0].as_ref().unwrap();
+        assert_eq!(game.get_tag("Event"), Some("Test \"quoted\""));
+        assert_eq!(game.get_tag("ECO"), Some("C60"));
+        assert_eq!(game.result, "1-0");
+        assert_eq!(move_strings(game), ["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5a4"]);
+
+        let game = games[1].as_ref().unwrap();
+        assert_eq!(game.result, "1/2-1/2");
+        assert_eq!(game.start_position.fullmove_number, 10);
+        assert_eq!(move_strings(game), ["e8d7", "e2e4"]);
+        assert_eq!(FenString::from(&game.get_final_position()).to_string(), "8/3k4/8/8/4P3/8/8/4K3 b - e3 0 11");
+    }
+
+    #[test]
+    fn pgn_errors_have_line_numbers() {
+        let games = read_games("[Event \"?\"]\n\n1. e4 e5\n2. Nf6 Nc6 1-0\n\n1. d4 d5 0-1\n");
+        assert!(matches!(&games[0], Err(PgnParseError::Move(4, SanParseError::IllegalMove(_)))));
+        assert!(games[1].is_ok());
+
+        let games = read_games("1. e4 e5\n\n[Event \"?\"]\n1. d4 *\n");
+        assert!(matches!(games[0], Err(PgnParseError::MissingResult(3))));
+        assert!(games[1].is_ok());
+
+        assert!(matches!(read_games("[Event ?]\n1. e4 *")[0], Err(PgnParseError::Tag(1, _))));
+        assert!(matches!(read_games("[FEN \"8/8 w\"]\n*")[0], Err(PgnParseError::Fen(1, _))));
+        assert!(matches!(read_games("1. e4\ne5) *")[0], Err(PgnParseError::Token(2, _))));
+        assert!(matches!(read_games("1. e4 e5\n")[..], [Err(PgnParseError::MissingResult(1))]));
+    }
+
+    #[test]
+    fn pgn_games_are_written() {
+        let games = read_games(PGN);
+        let game = games[0].as_ref().unwrap();
+        assert_eq!(
+            game.to_string(),
+            "[Event \"Test \\\"quoted\\\"\"]\n[Site \"?\"]\n[Date \"????.??.??\"]\n[Round \"?\"]\n[White \"?\"]\n[Black \"?\"]\n[Result \"1-0\"]\n[ECO \"C60\"]\n\n\
+            1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 1-0\n"
+        );
+
+        let game = games[1].as_ref().unwrap();
+        assert!(game.to_string().contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 10\"]\n"));
+        assert!(game.to_string().ends_with("\n\n10... Kd7 11. e4 1/2-1/2\n"));
+    }
+
+    #[test]
+    fn written_pgn_games_are_read_back() {
+        let mut position = Position::starting_position();
+        let mut moves = Vec::new();
+        for san in ["d4", "Nf6", "c4", "e6", "Nc3", "Bb4", "Qc2", "O-O", "a3", "Bxc3+", "Qxc3", "b6", "Bg5", "Bb7", "f3", "h6", "Bh4", "d5", "e3", "Nbd7", "cxd5", "Nxd5"] {
+            let bit_move = position.parse_san(san).unwrap();
+            position.make_move(bit_move);
+            moves.push(bit_move);
+        }
+
+        let game = PgnGame::new(Position::starting_position(), moves, "*");
+        let mut bytes = Vec::new();
+        game.write(&mut bytes).unwrap();
+        game.write(&mut bytes).unwrap();
+        let pgn = String::from_utf8(bytes).unwrap();
+        assert!(pgn.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+
+        let games = read_games(&pgn);
+        assert_eq!(games.len(), 2);
+        for read_game in games {
+            assert_eq!(read_game.unwrap().moves, game.moves);
+        }
+    }
+
+    #[test]
+    fn opening_pgn_games_are_read() {
+        let games = read_games(OPENING_PGN);
+        assert_eq!(games.len(), 1);
+
+        let game = games[0].as_ref().unwrap();
+        assert_eq!(game.get_tag("PlyCount"), Some("82"));
+        assert_eq!(game.moves.len(), 82);
+        assert_eq!(game.result, "1-0");
+    }
+
+    // NOTE: The opening games aren't part of the package, so this only runs on request
+    #[test]
+    #[ignore]
+    fn opening_pgn_file_is_read() {
+        let games = PgnReader::from_file("openings/openings.pgn").unwrap();
+        assert!(games.map(Result::unwrap).count() > 0);
+    }
+}
@@ -2,6 +2,8 @@ use core::fmt;
 
 use crate::{BitMove, Bitboard, CastlingRights, Color, EvalPosition, FenString, File, MoveFlag, MoveMasks, Piece, Square, ZobristKey, PIECE_TYPE_COUNT, SQUARE_COUNT};
 
+const FIFTY_MOVE_RULE_HALFMOVES: u16 = 100;
+
 #[derive(Clone)]
 pub struct Position {
     #[cfg(feature = "bb_array")]
@@ -15,6 +17,8 @@ pub struct Position {
     pub en_passant_option: Option<Square>,
     pub castling_rights: CastlingRights,
     pub zobrist_key: ZobristKey,
+    pub halfmove_clock: u16,
+    pub fullmove_number: u16,
     pub(crate) ply: u16,
 
     #[cfg(feature = "tapered_eval")]
@@ -34,6 +38,8 @@ impl Default for Position {
             side: Color::White,
             en_passant_option: None,
             castling_rights: CastlingRights::NONE,
+            halfmove_clock: 0,
+            fullmove_number: 1,
             ply: 0,
             zobrist_key: ZobristKey(0),
 
@@ -101,6 +107,8 @@ impl Position {
             side: Color::White,
             en_passant_option: None,
             castling_rights: CastlingRights::DEFAULT,
+            halfmove_clock: 0,
+            fullmove_number: 1,
             ply: 0,
             zobrist_key: ZobristKey(0),
             
@@ -166,6 +174,18 @@ impl Position {
         // Modify the zobrist key before making the move
         self.zobrist_mods();
 
+        // Updates the move counters
+        // NOTE: En-passant moves are pawn moves, so they are also covered by this check
+        if piece == Piece::WP || piece == Piece::BP || capture_option.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if self.side == Color::Black {
+            self.fullmove_number += 1;
+        }
+
         // Removes captured piece
         // NOTE: Because of the way zobrist hashing is implemented,
         // it is important that the capture is removed before moving the piece.
@@ -374,6 +394,11 @@ impl Position {
             }
         };
 
+        // NOTE: The halfmove clock can't be restored from the move alone and is left as is
+        if self.side == Color::Black {
+            self.fullmove_number -= 1;
+        }
+
         self.castling_rights = old_castling_rights;
         self.populate_occupancies();
     }
@@ -405,6 +430,27 @@ impl Position {
         (MoveMasks::get_king_mask(square) & self.bitboards[enemy_king]).is_not_empty()
     }
 
+    // NOTE: Returns the attackers of both sides, where sliding pieces are blocked by the given
+    // occupancy instead of the current one, so pieces can be removed to discover x-ray attackers
+    #[inline(always)]
+    pub(crate) fn get_attackers(&self, square: Square, occupancy: Bitboard) -> Bitboard {
+        let bishops_and_queens = self.bitboards[Piece::WB] | self.bitboards[Piece::BB] | self.bitboards[Piece::WQ] | self.bitboards[Piece::BQ];
+        let rooks_and_queens = self.bitboards[Piece::WR] | self.bitboards[Piece::BR] | self.bitboards[Piece::WQ] | self.bitboards[Piece::BQ];
+
+        ((MoveMasks::get_pawn_capture_mask(Color::White, square) & self.bitboards[Piece::BP]) |
+        (MoveMasks::get_pawn_capture_mask(Color::Black, square) & self.bitboards[Piece::WP]) |
+        (MoveMasks::get_knight_mask(square) & (self.bitboards[Piece::WN] | self.bitboards[Piece::BN])) |
+        (MoveMasks::get_bishop_mask(square, occupancy) & bishops_and_queens) |
+        (MoveMasks::get_rook_mask(square, occupancy) & rooks_and_queens) |
+        (MoveMasks::get_king_mask(square) & (self.bitboards[Piece::WK] | self.bitboards[Piece::BK]))) &
+        occupancy
+    }
+
+    #[inline(always)]
+    pub fn is_fifty_move_draw(&self) -> bool {
+        self.halfmove_clock >= FIFTY_MOVE_RULE_HALFMOVES
+    }
+
     pub fn in_check(&self, defending_side: Color) -> bool {
         match defending_side {
             Color::White => self.is_square_attacked(defending_side, Square::from(self.bitboards[Piece::WK])),
@@ -471,14 +517,47 @@ impl fmt::Display for Position {
          Side: {}
    En-passant: {:?}
      Castling: {}
+     Halfmove: {}
+     Fullmove: {}
   Zobrist Key: {:#x}\n",
             FenString::from(self),
             self.side,
             self.en_passant_option,
             self.castling_rights,
+            self.halfmove_clock,
+            self.fullmove_number,
             self.zobrist_key.0,
         );
         
         f.pad(&s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{Legal, MoveGeneration, Uci};
+
+    use super::*;
+
+    fn make_uci_move(position: &mut Position, move_string: &str) {
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(position);
+        position.make_move(Uci::parse_move_string(&legal_moves, move_string).unwrap());
+    }
+
+    #[test]
+    fn make_move_updates_move_counters() {
+        let mut position = Position::starting_position();
+
+        make_uci_move(&mut position, "g1f3");
+        assert_eq!((position.halfmove_clock, position.fullmove_number), (1, 1));
+
+        make_uci_move(&mut position, "g8f6");
+        assert_eq!((position.halfmove_clock, position.fullmove_number), (2, 2));
+
+        make_uci_move(&mut position, "e2e4");
+        assert_eq!((position.halfmove_clock, position.fullmove_number), (0, 2));
+
+        make_uci_move(&mut position, "f6e4");
+        assert_eq!((position.halfmove_clock, position.fullmove_number), (0, 3));
+    }
+}
@@ -0,0 +1,22 @@
+use crate::{BitMove, ScoringMove};
+
+#[derive(Clone, Debug)]
+pub struct PvLine {
+    pub scoring_move: ScoringMove,
+    pub pv: Vec<BitMove>,
+}
+
+impl PvLine {
+    #[inline(always)]
+    pub fn new(scoring_move: ScoringMove, pv: Vec<BitMove>) -> PvLine {
+        PvLine { scoring_move, pv }
+    }
+
+    pub fn to_uci_string(&self) -> String {
+        self.pv
+            .iter()
+            .map(|bit_move| bit_move.to_uci_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{BitMove, MAX_DEPTH};
+
+// NOTE: The principal variation found at each ply is stored in its own row of a triangular
+// table, starting at the index of the ply. When a move improves alpha, the row is replaced by
+// the move followed by the principal variation of the child, which is one row further down.
+#[derive(Clone)]
+pub(crate) struct PvTable {
+    moves: Box<[[BitMove; MAX_DEPTH]; MAX_DEPTH]>,
+    lengths: [usize; MAX_DEPTH],
+}
+
+impl Default for PvTable {
+    fn default() -> PvTable {
+        PvTable {
+            moves: Box::new([[BitMove::EMPTY; MAX_DEPTH]; MAX_DEPTH]),
+            lengths: [0; MAX_DEPTH],
+        }
+    }
+}
+
+impl PvTable {
+    #[inline(always)]
+    pub(crate) fn clear_ply(&mut self, ply: u16) {
+        let ply = ply as usize;
+        if ply < MAX_DEPTH {
+            self.lengths[ply] = ply;
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn update(&mut self, ply: u16, bit_move: BitMove) {
+        let ply = ply as usize;
+        if ply >= MAX_DEPTH {
+            return;
+        }
+
+        let child_length = if ply + 1 < MAX_DEPTH {
+            self.lengths[ply + 1].max(ply + 1)
+        } else {
+            ply + 1
+        };
+
+        self.moves[ply][ply] = bit_move;
+        for index in ply + 1..child_length {
+            self.moves[ply][index] = self.moves[ply + 1][index];
+        }
+        self.lengths[ply] = child_length;
+    }
+
+    #[inline(always)]
+    pub(crate) fn get_root_pv(&self) -> &[BitMove] {
+        &self.moves[0][..self.lengths[0]]
+    }
+}
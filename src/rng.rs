@@ -1,3 +1,4 @@
+#[derive(Clone)]
 pub struct RandomNumberGenerator {
     state: u32
 }
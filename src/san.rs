@@ -0,0 +1,253 @@
+use crate::{BitMove, Legal, MoveFlag, MoveGeneration, Piece, Position, SanParseError, Square};
+
+const PROMOTION_PIECE_CHARS: [char; 4] = ['N', 'B', 'R', 'Q'];
+
+// NOTE: Pieces are written in SAN by the uppercase char of their type, regardless of color
+#[inline(always)]
+fn piece_type_char(piece: Piece) -> char {
+    char::from(piece).to_ascii_uppercase()
+}
+
+#[inline(always)]
+fn promotion_char_option(bit_move: BitMove) -> Option<char> {
+    match bit_move.flag_option() {
+        Some(MoveFlag::PromoN) => Some('N'),
+        Some(MoveFlag::PromoB) => Some('B'),
+        Some(MoveFlag::PromoR) => Some('R'),
+        Some(MoveFlag::PromoQ) => Some('Q'),
+        _ => None,
+    }
+}
+
+impl BitMove {
+    // Formats the move in Standard Algebraic Notation, which depends on the position the move is
+    // played in: https://www.chessprogramming.org/Algebraic_Chess_Notation#Standard_Algebraic_Notation_.28SAN.29
+    pub fn to_san_string(self, position: &Position) -> String {
+        let source = self.source();
+        let target = self.target();
+        let piece = position.get_piece_option(source).expect("Move should have a piece on its source square");
+        let flag_option = self.flag_option();
+        let is_capture = self.is_capture(position) || flag_option.is_some_and(|flag| flag.is_en_passant());
+
+        let mut san = match flag_option {
+            Some(MoveFlag::WKCastle | MoveFlag::BKCastle) => String::from("O-O"),
+            Some(MoveFlag::WQCastle | MoveFlag::BQCastle) => String::from("O-O-O"),
+            _ if piece == Piece::WP || piece == Piece::BP => {
+                let mut san = String::new();
+                if is_capture {
+                    san.push_str(&source.to_string()[..1]);
+                    san.push('x');
+                }
+                san.push_str(&target.to_string());
+                if let Some(promotion_char) = promotion_char_option(self) {
+                    san.push('=');
+                    san.push(promotion_char);
+                }
+                san
+            },
+            _ => {
+                let mut san = String::from(piece_type_char(piece));
+                san.push_str(&Self::get_disambiguation(position, self, piece));
+                if is_capture {
+                    san.push('x');
+                }
+                san.push_str(&target.to_string());
+                san
+            },
+        };
+
+        let mut next_position = position.clone();
+        next_position.make_move(self);
+        if next_position.in_check(next_position.side) {
+            match MoveGeneration::generate_moves::<BitMove, Legal>(&next_position).is_empty() {
+                true => san.push('#'),
+                false => san.push('+'),
+            }
+        }
+
+        san
+    }
+
+    // NOTE: The file of the source square is preferred, then its rank, and both are only used if
+    // neither is unique among the pieces of the same type that can move to the target square
+    fn get_disambiguation(position: &Position, bit_move: BitMove, piece: Piece) -> String {
+        let source_string = bit_move.source().to_string();
+        let other_sources: Vec<String> = MoveGeneration::generate_moves::<BitMove, Legal>(position)
+            .iter()
+            .filter(|other_move| {
+                other_move.target() == bit_move.target() &&
+                other_move.source() != bit_move.source() &&
+                position.get_piece_option(other_move.source()) == Some(piece)
+            })
+            .map(|other_move| other_move.source().to_string())
+            .collect();
+
+        if other_sources.is_empty() {
+            String::new()
+        } else if other_sources.iter().all(|other_source| other_source[..1] != source_string[..1]) {
+            source_string[..1].to_string()
+        } else if other_sources.iter().all(|other_source| other_source[1..] != source_string[1..]) {
+            source_string[1..].to_string()
+        } else {
+            source_string
+        }
+    }
+}
+
+impl Position {
+    // NOTE: Check, checkmate and annotation suffixes are ignored, as are redundant disambiguations
+    // and capture signs, so moves are only rejected if they aren't legal or are ambiguous
+    pub fn parse_san(&self, san: &str) -> Result<BitMove, SanParseError> {
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(self);
+        let trimmed_san = san.trim().trim_end_matches(['+', '#', '!', '?']);
+        if trimmed_san.is_empty() {
+            return Err(SanParseError::Empty);
+        }
+
+        let castle_flag_option = match trimmed_san {
+            "O-O" | "0-0" => Some([MoveFlag::WKCastle, MoveFlag::BKCastle]),
+            "O-O-O" | "0-0-0" => Some([MoveFlag::WQCastle, MoveFlag::BQCastle]),
+            _ => None,
+        };
+
+        if let Some(castle_flags) = castle_flag_option {
+            return legal_moves
+                .iter()
+                .find(|bit_move| bit_move.flag_option().is_some_and(|flag| castle_flags.contains(&flag)))
+                .copied()
+                .ok_or_else(|| SanParseError::IllegalMove(san.to_string()));
+        }
+
+        // NOTE: Promotions are also accepted without the equals sign, like in e8Q
+        let (trimmed_san, promotion_piece_char_option) = match trimmed_san.split_once('=') {
+            Some((trimmed_san, promotion_string)) => match promotion_string.chars().collect::<Vec<char>>()[..] {
+                [promotion_char] if PROMOTION_PIECE_CHARS.contains(&promotion_char) => (trimmed_san, Some(promotion_char)),
+                _ => return Err(SanParseError::PromotionPiece(promotion_string.to_string())),
+            },
+            None => match trimmed_san.char_indices().last() {
+                Some((index, promotion_char)) if PROMOTION_PIECE_CHARS.contains(&promotion_char) => (&trimmed_san[..index], Some(promotion_char)),
+                _ => (trimmed_san, None),
+            },
+        };
+
+        let (piece_char, trimmed_san) = match trimmed_san.chars().next() {
+            Some(piece_char @ ('N' | 'B' | 'R' | 'Q' | 'K')) => (piece_char, &trimmed_san[1..]),
+            _ => ('P', trimmed_san),
+        };
+
+        let target_index = trimmed_san.len().checked_sub(2).filter(|&index| trimmed_san.is_char_boundary(index))
+            .ok_or_else(|| SanParseError::Syntax(san.to_string()))?;
+        let target = Square::try_from(&trimmed_san[target_index..])?;
+        let disambiguation = trimmed_san[..target_index].trim_end_matches('x');
+        let (file_option, rank_option) = match disambiguation.chars().collect::<Vec<char>>()[..] {
+            [] => (None, None),
+            [file @ 'a'..='h'] => (Some(file), None),
+            [rank @ '1'..='8'] => (None, Some(rank)),
+            [file @ 'a'..='h', rank @ '1'..='8'] => (Some(file), Some(rank)),
+            _ => return Err(SanParseError::Syntax(san.to_string())),
+        };
+
+        let mut matching_moves = legal_moves.iter().filter(|bit_move| {
+            let source_string = bit_move.source().to_string();
+            bit_move.target() == target &&
+            promotion_char_option(**bit_move) == promotion_piece_char_option &&
+            file_option.is_none_or(|file| source_string.starts_with(file)) &&
+            rank_option.is_none_or(|rank| source_string.ends_with(rank)) &&
+            self.get_piece_option(bit_move.source()).is_some_and(|piece| piece_type_char(piece) == piece_char)
+        });
+
+        match (matching_moves.next(), matching_moves.next()) {
+            (Some(&bit_move), None) => Ok(bit_move),
+            (Some(_), Some(_)) => Err(SanParseError::AmbiguousMove(san.to_string())),
+            (None, _) => Err(SanParseError::IllegalMove(san.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FenString, Uci};
+
+    use super::*;
+
+    fn san_string(position: &Position, move_string: &str) -> String {
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(position);
+        Uci::parse_move_string(&legal_moves, move_string).unwrap().to_san_string(position)
+    }
+
+    #[test]
+    fn moves_are_formatted_as_san() {
+        let position: Position = FenString::kiwipete().parse().unwrap();
+        for (move_string, san) in [
+            ("e1g1", "O-O"),
+            ("e1c1", "O-O-O"),
+            ("e5f7", "Nxf7"),
+            ("e2a6", "Bxa6"),
+            ("g2h3", "gxh3"),
+            ("a2a3", "a3"),
+            ("f3f6", "Qxf6"),
+            ("e5g6", "Nxg6"),
+            ("d5e6", "dxe6"),
+        ] {
+            assert_eq!(san_string(&position, move_string), san, "{move_string}");
+        }
+
+        let position: Position = FenString::from("4k3/1P6/8/8/8/8/4K3/R6R w - - 0 1").parse().unwrap();
+        assert_eq!(san_string(&position, "b7b8q"), "b8=Q+");
+        assert_eq!(san_string(&position, "b7b8n"), "b8=N");
+        assert_eq!(san_string(&position, "a1d1"), "Rad1");
+        assert_eq!(san_string(&position, "h1h8"), "Rh8+");
+
+        let position: Position = FenString::from("k7/8/8/8/N3N3/8/N7/K7 w - - 0 1").parse().unwrap();
+        assert_eq!(san_string(&position, "a4c3"), "Na4c3");
+        assert_eq!(san_string(&position, "e4c3"), "Nec3");
+        assert_eq!(san_string(&position, "a2c3"), "N2c3");
+
+        let position: Position = FenString::from("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").parse().unwrap();
+        assert_eq!(san_string(&position, "a1a8"), "Ra8#");
+
+        let position: Position = FenString::from("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").parse().unwrap();
+        assert_eq!(san_string(&position, "e5d6"), "exd6");
+    }
+
+    #[test]
+    fn san_moves_are_parsed() {
+        let position: Position = FenString::kiwipete().parse().unwrap();
+        for (san, move_string) in [
+            ("O-O", "e1g1"),
+            ("0-0-0", "e1c1"),
+            ("Nxf7", "e5f7"),
+            ("Nf7+", "e5f7"),
+            ("Bxa6!?", "e2a6"),
+            ("gxh3", "g2h3"),
+            ("a3", "a2a3"),
+            ("Qxf6", "f3f6"),
+            ("Ne5xf7", "e5f7"),
+        ] {
+            assert_eq!(position.parse_san(san).unwrap().to_uci_string(), move_string, "{san}");
+        }
+
+        let position: Position = FenString::from("4k3/1P6/8/8/8/8/4K3/R6R w - - 0 1").parse().unwrap();
+        assert_eq!(position.parse_san("b8=N").unwrap().to_uci_string(), "b7b8n");
+        assert_eq!(position.parse_san("b8Q").unwrap().to_uci_string(), "b7b8q");
+        assert_eq!(position.parse_san("Rad1").unwrap().to_uci_string(), "a1d1");
+        assert!(matches!(position.parse_san("Rd1"), Err(SanParseError::AmbiguousMove(_))));
+        assert!(matches!(position.parse_san("b8"), Err(SanParseError::IllegalMove(_))));
+        assert!(matches!(position.parse_san("b8=K"), Err(SanParseError::PromotionPiece(_))));
+        assert!(matches!(position.parse_san("Nf6"), Err(SanParseError::IllegalMove(_))));
+        assert!(matches!(position.parse_san("O-O"), Err(SanParseError::IllegalMove(_))));
+        assert!(matches!(position.parse_san("Rz1"), Err(SanParseError::SquareParseError(_))));
+        assert!(matches!(position.parse_san("R"), Err(SanParseError::Syntax(_))));
+        assert!(matches!(position.parse_san("+"), Err(SanParseError::Empty)));
+    }
+
+    #[test]
+    fn san_strings_round_trip() {
+        for fen in [FenString::startpos(), FenString::kiwipete(), FenString::tricky()] {
+            let position: Position = fen.parse().unwrap();
+            for &bit_move in MoveGeneration::generate_moves::<BitMove, Legal>(&position).iter() {
+                assert_eq!(position.parse_san(&bit_move.to_san_string(&position)).unwrap(), bit_move);
+            }
+        }
+    }
+}
@@ -14,6 +14,8 @@ impl Score {
     pub(crate) const REPETITION: Score = Score(0);
     pub(crate) const START_ALPHA: Score = Score(-32001);
     pub(crate) const START_BETA: Score = Score(32001);
+    #[cfg(feature = "syzygy_tablebase")]
+    pub(crate) const TABLEBASE_WIN: Score = Score(9000);
     
     #[inline(always)]
     pub fn abs(self) -> Score {
@@ -35,6 +37,14 @@ impl Score {
         self.abs() >= Score::checkmate_minus_depth(MAX_DEPTH)
     }
 
+    // NOTE: Mate scores are offset from the checkmate score by the number of plies until mate,
+    // which is converted to signed full moves as expected by the uci protocol.
+    #[inline(always)]
+    pub fn moves_to_mate(self) -> i16 {
+        let plies_to_mate = (Self::CHECKMATE - self.abs()).0.max(1);
+        (plies_to_mate + 1) / 2 * self.signum().0
+    }
+
     #[inline(always)]
     pub fn is_positive(self) -> bool {
         self.0 > 0
@@ -129,3 +139,18 @@ impl Display for Score {
         write!(f, "{}", self.0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_to_mate_is_signed_and_rounded_up() {
+        assert_eq!(Score::checkmate_minus_depth(1).moves_to_mate(), 1);
+        assert_eq!(Score::checkmate_minus_depth(3).moves_to_mate(), 2);
+        assert_eq!(Score::checkmate_minus_depth(5).moves_to_mate(), 3);
+        assert_eq!((-Score::checkmate_minus_depth(2)).moves_to_mate(), -1);
+        assert_eq!((-Score::checkmate_minus_depth(4)).moves_to_mate(), -2);
+        assert_eq!(Score::CHECKMATE.moves_to_mate(), 1);
+    }
+}
@@ -1,8 +1,8 @@
-use std::{cmp::min, sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex}};
+use std::{cmp::min, sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc, Mutex}};
 
-use crate::{BitMove, EvalPosition, HistoryHeuristic, KillerMoves, Legal, MoveGeneration, Position, PseudoLegal, Score, ScoringMove, TTData, TTNodeType, Timer, TranspositionTable, ZobristKey, MAX_DEPTH, SQUARE_COUNT};
+use crate::time_manager::DEFAULT_MOVE_OVERHEAD_MS;
+use crate::{BitMove, EvalMove, EvalPosition, Legal, MoveGeneration, MoveList, Position, PseudoLegal, PvLine, PvTable, Score, ScoringMove, SearchContext, SearchLimits, Skill, TTData, TTNodeType, TimeManager, Timer, ZobristKey, MAX_DEPTH, SQUARE_COUNT};
 
-const AVERAGE_AMOUNT_OF_MOVES: usize = 25;
 const NULL_MOVE_DEPTH_REDUCTION: usize = 3;
 const LMR_MOVE_INDEX_THRESHOLD: usize = 3;
 const TABLEBASE_SEARCH_THRESHOLD: u128 = 100;
@@ -11,25 +11,44 @@ const OPENING_BOOK_SEARCH_THRESHOLD: u128 = 100;
 const LMR_DEPTH_THRESHOLD: usize = 3;
 const LMR_FACTOR: f32 = 0.75;
 const NUM_NODE_CHECK: u64 = 10000;
-
-#[cfg(not(feature = "late_move_reductions"))]
-const AVERAGE_BRANCHING_FACTOR: usize = 5;
-
-#[cfg(feature = "late_move_reductions")]
-const AVERAGE_BRANCHING_FACTOR: usize = 2;
+const DEFAULT_MULTI_PV: usize = 1;
+const INFINITE_SEARCH_POLL_MS: u64 = 5;
+const PONDER_NOT_HIT: u64 = u64::MAX;
+const ASPIRATION_WINDOW: i16 = 50;
+const ASPIRATION_MAX_WINDOW: i16 = 800;
+const ASPIRATION_DEPTH_THRESHOLD: usize = 4;
 
 #[derive(Clone)]
 pub struct Search {
     nodes: u64,
     pub(crate) zobrist_key_history: Vec<ZobristKey>,
+    repetition_history_start: usize,
     timer: Arc<Timer>,
-    stop_time: Arc<Option<u128>>,
+    time_manager: TimeManager,
+    move_overhead: u128,
     stop_calculating: Arc<AtomicBool>,
     pub(crate) in_opening: bool,
+    multi_pv: usize,
+    excluded_root_moves: Vec<BitMove>,
+    limits: SearchLimits,
+    pub(crate) context: SearchContext,
+    pv: Vec<BitMove>,
+    pv_table: PvTable,
+    ponder_hit: Arc<AtomicBool>,
+    ponder_hit_time: Arc<AtomicU64>,
+    best_move_found: Arc<Mutex<(BitMove, u128)>>,
+    show_ponder_move: bool,
+    skill: Skill,
     
     #[cfg(feature = "opening_book")]
     opening_book: Arc<crate::OpeningBook>,
 
+    #[cfg(feature = "opening_book")]
+    own_book: bool,
+
+    #[cfg(feature = "opening_book")]
+    best_book_move: bool,
+
     #[cfg(feature = "syzygy_tablebase")]
     tablebase: Arc<Option<crate::SyzygyTablebase>>,
     
@@ -40,14 +59,33 @@ impl Default for Search {
     fn default() -> Search {
         Search {
             timer: Arc::new(Timer::new()),
-            stop_time: Arc::new(None),
+            time_manager: TimeManager::default(),
+            move_overhead: DEFAULT_MOVE_OVERHEAD_MS,
             stop_calculating: Arc::new(AtomicBool::new(false)),
             nodes: 0,
             zobrist_key_history: Vec::new(),
+            repetition_history_start: 0,
             in_opening: true,
+            multi_pv: DEFAULT_MULTI_PV,
+            excluded_root_moves: Vec::new(),
+            limits: SearchLimits::default(),
+            context: SearchContext::default(),
+            pv: Vec::new(),
+            pv_table: PvTable::default(),
+            ponder_hit: Arc::new(AtomicBool::new(false)),
+            ponder_hit_time: Arc::new(AtomicU64::new(PONDER_NOT_HIT)),
+            best_move_found: Arc::new(Mutex::new((BitMove::EMPTY, 0))),
+            show_ponder_move: false,
+            skill: Skill::default(),
             
             #[cfg(feature = "opening_book")]
             opening_book: Arc::new(crate::OpeningBook::default()),
+
+            #[cfg(feature = "opening_book")]
+            own_book: true,
+
+            #[cfg(feature = "opening_book")]
+            best_book_move: false,
             
             #[cfg(feature = "syzygy_tablebase")]
             tablebase: Arc::new(crate::SyzygyTablebase::from_directory("tables/syzygy").ok()),
@@ -98,6 +136,55 @@ impl Search {
         self.uci_visible = false;
     }
 
+    #[inline(always)]
+    pub fn set_multi_pv(&mut self, multi_pv: usize) {
+        self.multi_pv = multi_pv.max(1);
+    }
+
+    // NOTE: Skill levels range from 0 to 20, where 20 is full strength
+    #[inline(always)]
+    pub fn set_skill_level(&mut self, skill_level: u8) {
+        self.skill.set_level(skill_level);
+    }
+
+    #[inline(always)]
+    pub fn set_elo(&mut self, elo: u16) {
+        self.skill.set_level(Skill::level_from_elo(elo));
+    }
+
+    #[inline(always)]
+    pub fn set_move_overhead(&mut self, move_overhead: u128) {
+        self.move_overhead = move_overhead;
+    }
+
+    // NOTE: Clears the transposition table and move ordering heuristics, which should be done
+    // before starting a new game.
+    #[inline(always)]
+    pub fn clear_tables(&mut self) {
+        self.context.reset();
+    }
+
+    #[inline(always)]
+    pub fn clear_transposition_table(&self) {
+        self.context.clear_transposition_table();
+    }
+
+    #[inline(always)]
+    pub fn resize_transposition_table(&mut self, size_mb: usize) {
+        self.context.resize_transposition_table(size_mb);
+    }
+
+    #[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
+    #[inline(always)]
+    pub fn set_num_threads(&mut self, num_threads: usize) {
+        self.context.set_num_threads(num_threads);
+    }
+
+    #[inline(always)]
+    pub fn set_ponder(&mut self, ponder: bool) {
+        self.show_ponder_move = ponder;
+    }
+
     #[inline(always)]
     pub fn begin_stop_calculating(&self) {
         self.stop_calculating.store(true, Ordering::Relaxed);
@@ -112,37 +199,134 @@ impl Search {
     pub fn get_stop_calculating(&self) -> Arc<AtomicBool> {
         self.stop_calculating.clone()
     }
+
+    #[inline(always)]
+    pub fn get_ponder_hit(&self) -> Arc<AtomicBool> {
+        self.ponder_hit.clone()
+    }
+
+    // NOTE: While pondering, the time budget only starts counting once the ponderhit arrives,
+    // which is registered by the first thread to notice it.
+    #[inline(always)]
+    fn time_budget_start(&self) -> Option<u128> {
+        if !self.limits.ponder {
+            return Some(0);
+        }
+
+        if !self.ponder_hit.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let time_passed = self.timer.get_time_passed_millis() as u64;
+        match self.ponder_hit_time.compare_exchange(PONDER_NOT_HIT, time_passed, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => Some(time_passed as u128),
+            Err(ponder_hit_time) => Some(ponder_hit_time as u128),
+        }
+    }
+
+    #[inline(always)]
+    fn is_pondering(&self) -> bool {
+        self.time_budget_start().is_none()
+    }
     
+    #[inline(always)]
+    fn add_heuristic_scores(&self, position: &Position, moves: &mut MoveList<ScoringMove>) {
+        for scoring_move in moves.iter_mut() {
+            scoring_move.score += EvalMove::eval_heuristics(position, scoring_move.bit_move, &self.context);
+        }
+    }
+
     #[inline(always)]
     fn move_ordering_best_move(&self, position: &Position) -> ScoringMove {
         let mut moves = MoveGeneration::generate_moves::<ScoringMove, Legal>(position);
+        self.add_heuristic_scores(position, &mut moves);
         moves.sort_by_score();
-        moves.first()
+        moves
+            .into_iter()
+            .find(|scoring_move| self.is_root_move_allowed(position, scoring_move.bit_move))
+            .unwrap_or(ScoringMove::blank(Score::BLANK))
+    }
+
+    // NOTE: Root moves can be excluded to search for the next best line in multi-pv mode,
+    // and they can be restricted to the moves given with searchmoves.
+    #[inline(always)]
+    fn is_root_move_allowed(&self, position: &Position, bit_move: BitMove) -> bool {
+        position.ply != 0 || (
+            !self.excluded_root_moves.contains(&bit_move) &&
+            (self.limits.search_moves.is_empty() || self.limits.search_moves.contains(&bit_move))
+        )
+    }
+
+    #[inline(always)]
+    fn has_restricted_root_moves(&self, position: &Position) -> bool {
+        position.ply == 0 && (!self.excluded_root_moves.is_empty() || !self.limits.search_moves.is_empty())
     }
     
+    // NOTE: Without a search, the pv is made up of the best moves by move ordering for both sides.
+    #[cfg(all(not(feature = "minimax"), not(feature = "negamax")))]
+    fn move_ordering_best_line(&mut self, position: &Position, depth: usize) -> ScoringMove {
+        let mut line = Vec::new();
+        let mut position_copy = position.clone();
+        for _ in 0..depth.max(1) {
+            let scoring_move = self.move_ordering_best_move(&position_copy);
+            if scoring_move.bit_move == BitMove::EMPTY {
+                break;
+            }
+
+            line.push(scoring_move);
+            position_copy.make_move(scoring_move.bit_move);
+            position_copy.ply += 1;
+        }
+
+        self.pv_table.clear_ply(position.ply + line.len() as u16);
+        for (index, scoring_move) in line.iter().enumerate().rev() {
+            self.pv_table.update(position.ply + index as u16, scoring_move.bit_move);
+        }
+
+        line.first().copied().unwrap_or(ScoringMove::blank(Score::BLANK))
+    }
+
     #[inline(always)]
     fn minimax_best_move(&mut self, position: &Position, depth: usize) -> ScoringMove {
+        self.pv_table.clear_ply(position.ply);
+
         if depth == 0 {
             return ScoringMove::blank(EvalPosition::eval(position));
         }
 
         self.nodes += 1;
 
+        self.perform_stop_calculating_check();
         if self.should_stop_calculating() {
             return ScoringMove::blank(Score::BLANK);
         }
     
-        MoveGeneration::generate_moves::<ScoringMove, Legal>(position)
+        let moves = MoveGeneration::generate_moves::<ScoringMove, Legal>(position)
             .into_iter()
-            .map(|mut m: ScoringMove| {
-                let mut position_copy = position.clone();
-                position_copy.make_move(m.bit_move);
-                m.score = -self.minimax_best_move(&position_copy, depth - 1).score;
-                m
-            })
-            .max()
+            .filter(|m| self.is_root_move_allowed(position, m.bit_move))
+            .collect::<Vec<_>>();
+    
+        let mut best_move: Option<ScoringMove> = None;
+        for mut m in moves {
+            let mut position_copy = position.clone();
+            position_copy.make_move(m.bit_move);
+            position_copy.ply += 1;
+            m.score = -self.minimax_best_move(&position_copy, depth - 1).score;
+            if self.should_stop_calculating() {
+                break;
+            }
+
+            if best_move.is_none_or(|best_move| m.score >= best_move.score) {
+                best_move = Some(m);
+                self.pv_table.update(position.ply, m.bit_move);
+            }
+        }
+
+        best_move
             .unwrap_or_else(|| {
-                if position.in_check(position.side) {
+                if self.should_stop_calculating() {
+                    ScoringMove::blank(Score::BLANK)
+                } else if position.in_check(position.side) {
                     ScoringMove::blank(-Score::CHECKMATE)
                 } else {
                     ScoringMove::blank(Score::STALEMATE)
@@ -152,9 +336,13 @@ impl Search {
 
     #[inline(always)]
     fn perform_stop_calculating_check(&self) {
-        if let Some(stop_time) = *self.stop_time {
-            if self.nodes % NUM_NODE_CHECK == 0 {
-                if self.timer.get_time_passed_millis() >= stop_time {
+        if self.limits.nodes.is_some_and(|node_limit| self.nodes >= node_limit) {
+            self.begin_stop_calculating();
+        }
+
+        if self.time_manager.hard_limit().is_some() && self.nodes.is_multiple_of(NUM_NODE_CHECK) {
+            if let Some(start_time) = self.time_budget_start() {
+                if self.time_manager.is_hard_limit_reached(self.timer.get_time_passed_millis() - start_time) {
                     self.begin_stop_calculating();
                 }
             }
@@ -163,30 +351,58 @@ impl Search {
 
     #[inline(always)]
     fn quiescence(&mut self, position: &Position, mut alpha: Score, beta: Score) -> ScoringMove {
+        self.pv_table.clear_ply(position.ply);
         self.perform_stop_calculating_check();
         if self.should_stop_calculating() {
             return ScoringMove::blank(Score::BLANK);
         }
         
-        let evaluation = EvalPosition::eval(position);
+        // NOTE: The side to move can't stand pat while in check, so all evasions are searched
+        let in_check = cfg!(feature = "check_evasions") && position.in_check(position.side);
 
-        if evaluation >= beta {
-            return ScoringMove::blank(beta);
-        } else if evaluation > alpha {
-            alpha = evaluation;
+        if !in_check {
+            let evaluation = EvalPosition::eval(position);
+
+            if evaluation >= beta {
+                return ScoringMove::blank(beta);
+            } else if evaluation > alpha {
+                alpha = evaluation;
+            }
         }
 
         let mut best_move = ScoringMove::blank(alpha);
-        let mut moves = MoveGeneration::generate_captures::<ScoringMove, PseudoLegal>(position);
+        let mut moves = if in_check {
+            MoveGeneration::generate_evasions::<ScoringMove, PseudoLegal>(position)
+        } else {
+            MoveGeneration::generate_captures::<ScoringMove, PseudoLegal>(position)
+        };
+        self.add_heuristic_scores(position, &mut moves);
 
         #[cfg(feature = "sort_moves")]
         moves.sort_by_score();
 
+        let mut moves_has_legal_move = false;
         for scoring_capture in moves.iter_mut() {
+            #[cfg(not(feature = "quiescence_en_passant"))]
+            if !in_check && scoring_capture.bit_move.flag_option().is_some_and(crate::MoveFlag::is_en_passant) {
+                continue;
+            }
+
+            #[cfg(feature = "see")]
+            if !in_check && EvalMove::see(position, scoring_capture.bit_move) < Score::ZERO {
+                continue;
+            }
+
             let mut new_position = position.clone();
             if new_position.apply_pseudo_legal_move(scoring_capture.bit_move) {
+                moves_has_legal_move = true;
                 self.nodes += 1;
                 scoring_capture.score = -self.quiescence(&new_position, -beta, -alpha).score;
+
+                if scoring_capture.score.is_checkmate() {
+                    scoring_capture.score -= scoring_capture.score.signum();
+                }
+
                 if scoring_capture.score > alpha {
                     alpha = scoring_capture.score;
                     best_move = *scoring_capture;
@@ -197,15 +413,27 @@ impl Search {
             }
         }
 
+        if in_check && !moves_has_legal_move && !self.should_stop_calculating() {
+            return ScoringMove::blank(-Score::CHECKMATE);
+        }
+
         best_move
     }
 
     #[inline(always)]
     fn negamax_best_move(&mut self, position: &Position, mut alpha: Score, mut beta: Score, mut depth: usize) -> ScoringMove {
         self.nodes += 1;
+        self.pv_table.clear_ply(position.ply);
 
-        if self.zobrist_key_history.contains(&position.zobrist_key) {
-            return ScoringMove::blank(Score::REPETITION);
+        // NOTE: The root position is always searched to ensure a best move is returned
+        if position.ply > 0 {
+            if position.is_repetition(&self.zobrist_key_history[self.repetition_history_start..]) {
+                return ScoringMove::blank(Score::REPETITION);
+            }
+
+            if position.is_fifty_move_draw() || position.is_insufficient_material() {
+                return ScoringMove::blank(Score::DRAW);
+            }
         }
         
         if depth == 0 {
@@ -221,8 +449,12 @@ impl Search {
             return ScoringMove::blank(Score::BLANK);
         }
 
+        // NOTE: Entries for the root position don't take excluded root moves into account
+        #[cfg(feature = "tt")]
+        let tt_entry_option = self.context.transposition_table.probe(position.zobrist_key).filter(|_| !self.has_restricted_root_moves(position));
+
         #[cfg(feature = "tt")]
-        if let Some(tt_entry) = TranspositionTable::probe(position.zobrist_key) {
+        if let Some(tt_entry) = tt_entry_option {
             // If the stored depth is at least as deep, use it
             if tt_entry.depth >= depth as u16 {
                 match tt_entry.node_type {
@@ -259,16 +491,42 @@ impl Search {
             position_copy.side.switch();
             position_copy.en_passant_option = None;
             position_copy.zobrist_mods();
+
+            // NOTE: Repetitions are only scanned for among the positions after the null move,
+            // since it isn't a legal move and can't be part of an actual repetition
+            let repetition_history_start = self.repetition_history_start;
+            self.repetition_history_start = self.zobrist_key_history.len();
             let null_move_score = -self.negamax_best_move(&position_copy, -beta, -beta + 1, depth - NULL_MOVE_DEPTH_REDUCTION).score;
+            self.repetition_history_start = repetition_history_start;
             if null_move_score >= beta {
                 return ScoringMove::blank(beta);
             }
+
+            // NOTE: The null move search shares the ply of this node, so its pv has to be discarded
+            self.pv_table.clear_ply(position.ply);
         }
 
-        let mut moves = MoveGeneration::generate_moves::<ScoringMove, PseudoLegal>(position);
+        #[cfg(feature = "move_picker")]
+        let mut moves = crate::MovePicker::new(
+            position,
+            tt_entry_option.map_or(BitMove::EMPTY, |tt_entry| tt_entry.best_move.bit_move),
+            cfg!(feature = "check_evasions") && in_check,
+        );
 
-        #[cfg(feature = "sort_moves")]
-        moves.sort_by_score();
+        #[cfg(not(feature = "move_picker"))]
+        let mut moves = {
+            let mut moves = if cfg!(feature = "check_evasions") && in_check {
+                MoveGeneration::generate_evasions::<ScoringMove, PseudoLegal>(position)
+            } else {
+                MoveGeneration::generate_moves::<ScoringMove, PseudoLegal>(position)
+            };
+            self.add_heuristic_scores(position, &mut moves);
+
+            #[cfg(feature = "sort_moves")]
+            moves.sort_by_score();
+
+            moves.into_iter()
+        };
 
         #[cfg(feature = "history_heuristic")]
         let mut quiets_searched: [BitMove; SQUARE_COUNT] = [BitMove::EMPTY; SQUARE_COUNT];
@@ -279,7 +537,18 @@ impl Search {
         let mut best_move = ScoringMove::blank(alpha);
         self.zobrist_key_history.push(position.zobrist_key);
         let mut move_index = 0;
-        for mut scoring_move in moves {
+        #[allow(clippy::while_let_loop)]
+        loop {
+            #[cfg(feature = "move_picker")]
+            let Some(mut scoring_move) = moves.next(&self.context) else { break };
+
+            #[cfg(not(feature = "move_picker"))]
+            let Some(mut scoring_move) = moves.next() else { break };
+
+            if !self.is_root_move_allowed(position, scoring_move.bit_move) {
+                continue;
+            }
+
             let mut new_position = position.clone();
             if new_position.apply_pseudo_legal_move(scoring_move.bit_move) {
                 let is_capture_or_promotion = scoring_move.bit_move.is_capture_or_promotion(position);
@@ -294,14 +563,19 @@ impl Search {
                     // NOTE: Usually, we have to check if the new position is part of the PV, but since
                     // our TT returns exact scores early, this isn't needed.
                     reduced_depth = depth - min(depth, (LMR_FACTOR * (move_index as f32).ln() * (depth as f32).ln()) as usize);
-                    scoring_move.score = -self.negamax_best_move(&new_position, -beta, -alpha, reduced_depth).score;
+                    scoring_move.score = self.principal_variation_search(&new_position, alpha, beta, reduced_depth, move_index);
                 } else {
-                    scoring_move.score = -self.negamax_best_move(&new_position, -beta, -alpha, depth - 1).score;
+                    scoring_move.score = self.principal_variation_search(&new_position, alpha, beta, depth - 1, move_index);
                 }
 
                 #[cfg(not(feature = "late_move_reductions"))]
                 {
-                    scoring_move.score = -self.negamax_best_move(&new_position, -beta, -alpha, depth - 1).score;
+                    scoring_move.score = self.principal_variation_search(&new_position, alpha, beta, depth - 1, move_index);
+                }
+
+                // NOTE: The remaining moves would return right away without being searched
+                if self.should_stop_calculating() {
+                    break;
                 }
 
                 if scoring_move.score.is_checkmate() {
@@ -329,13 +603,14 @@ impl Search {
                     if should_update_alpha {
                         alpha = scoring_move.score;
                         best_move = scoring_move;
+                        self.pv_table.update(position.ply, scoring_move.bit_move);
                         if alpha >= beta {
                             if !is_capture_or_promotion {
                                 #[cfg(feature = "killer_heuristic")]
-                                KillerMoves::update(scoring_move.bit_move, new_position.ply);
+                                self.context.killer_moves.update(scoring_move.bit_move, new_position.ply);
                                 
                                 #[cfg(feature = "history_heuristic")]
-                                HistoryHeuristic::update(position.side, &quiets_searched[0..quiets_count], scoring_move.bit_move, depth as i16);
+                                self.context.history_heuristic.update(position.side, &quiets_searched[0..quiets_count], scoring_move.bit_move, depth as i16);
                             }
                             break;
                         }
@@ -353,6 +628,12 @@ impl Search {
         }
         self.zobrist_key_history.pop();
 
+        // NOTE: Scores of an aborted node are unreliable, so they must not end up in the
+        // transposition table, which is kept between searches
+        if self.should_stop_calculating() {
+            return best_move;
+        }
+
         if !moves_has_legal_move {
             if in_check {
                 best_move = ScoringMove::blank(-Score::CHECKMATE);
@@ -362,7 +643,7 @@ impl Search {
         }
 
         #[cfg(feature = "tt")]
-        {
+        if !self.has_restricted_root_moves(position) {
             let node_type = if best_move.score >= beta {
                 TTNodeType::LowerBound
             } else if best_move.score <= alpha {
@@ -371,7 +652,7 @@ impl Search {
                 TTNodeType::Exact
             };
     
-            TranspositionTable::store(
+            self.context.transposition_table.store(
                 position.zobrist_key,
                 TTData {
                     best_move,
@@ -384,10 +665,59 @@ impl Search {
         best_move
     }
 
+    // Searches every move after the first with a null window, since with good move ordering it
+    // should fail low, and only re-searches with the full window if it doesn't:
+    // https://www.chessprogramming.org/Principal_Variation_Search
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn principal_variation_search(&mut self, new_position: &Position, alpha: Score, beta: Score, depth: usize, move_index: usize) -> Score {
+        #[cfg(feature = "pvs")]
+        if move_index > 0 {
+            let score = -self.negamax_best_move(new_position, -alpha - 1, -alpha, depth).score;
+            if score <= alpha || score >= beta {
+                return score;
+            }
+        }
+
+        -self.negamax_best_move(new_position, -beta, -alpha, depth).score
+    }
+
+    // Searches the root with a window around the score of the previous iteration, which is
+    // widened on both sides whenever the search fails low or high:
+    // https://www.chessprogramming.org/Aspiration_Windows
+    #[cfg(feature = "aspiration_windows")]
+    fn aspiration_window_best_move(&mut self, position: &Position, depth: usize, previous_score: Score) -> ScoringMove {
+        let mut window = ASPIRATION_WINDOW;
+        while window <= ASPIRATION_MAX_WINDOW {
+            let alpha = Score::from(i16::from(previous_score).saturating_sub(window)).max(Score::START_ALPHA);
+            let beta = Score::from(i16::from(previous_score).saturating_add(window)).min(Score::START_BETA);
+            let best_move = self.negamax_best_move(position, alpha, beta, depth);
+
+            if self.should_stop_calculating() || (alpha < best_move.score && best_move.score < beta) {
+                return best_move;
+            }
+
+            window *= 2;
+        }
+
+        self.negamax_best_move(position, Score::START_ALPHA, Score::START_BETA, depth)
+    }
+
+    #[allow(unused_variables)]
+    #[inline(always)]
+    fn iteration_best_move(&mut self, position: &Position, depth: usize, previous_best_move: ScoringMove) -> ScoringMove {
+        #[cfg(feature = "aspiration_windows")]
+        if depth >= ASPIRATION_DEPTH_THRESHOLD && previous_best_move.bit_move != BitMove::EMPTY && !previous_best_move.score.is_checkmate() {
+            return self.aspiration_window_best_move(position, depth, previous_best_move.score);
+        }
+
+        self.best_move(position, depth)
+    }
+
     #[inline(always)]
     fn best_move(&mut self, position: &Position, depth: usize) -> ScoringMove {
         #[cfg(all(not(feature = "minimax"), not(feature = "negamax")))]
-        return self.move_ordering_best_move(position);
+        return self.move_ordering_best_line(position, depth);
 
         #[cfg(feature = "minimax")]
         return self.minimax_best_move(position, depth);
@@ -396,26 +726,61 @@ impl Search {
         return self.negamax_best_move(position, Score::START_ALPHA, Score::START_BETA, depth);
     }
 
-    fn reset(&mut self, stop_time: Option<u128>) {
-        self.stop_time = Arc::new(stop_time);
+    fn reset(&mut self, limits: SearchLimits) {
+        self.time_manager = TimeManager::new(&limits, self.move_overhead);
+        self.limits = limits;
         self.nodes = 0;
+        self.context.transposition_table.new_search();
+        self.pv.clear();
+        self.ponder_hit.store(false, Ordering::Relaxed);
+        self.ponder_hit_time.store(PONDER_NOT_HIT, Ordering::Relaxed);
         self.timer = Arc::new(Timer::new());
+        self.best_move_found = Arc::new(Mutex::new((BitMove::EMPTY, 0)));
         self.stop_calculating.store(false, Ordering::Relaxed);
     }
 
+    // NOTE: The time is only updated when the best move changes, so after a search it's the time
+    // at which the final best move was found
+    #[inline(always)]
+    fn update_best_move_found(&self, bit_move: BitMove) {
+        if let Ok(mut best_move_found) = self.best_move_found.lock() {
+            if best_move_found.0 != bit_move {
+                *best_move_found = (bit_move, self.timer.get_time_passed_millis());
+            }
+        }
+    }
+
+    pub fn get_best_move_found_time(&self) -> u128 {
+        self.best_move_found.lock().map_or(0, |best_move_found| best_move_found.1)
+    }
+
     #[inline(always)]
     fn go_no_iterative_deepening(&mut self, position: &Position, depth: usize) -> ScoringMove {
-        let best_move = self.best_move(position, depth);
-        uci_println!(self, "info depth {} score cp {} nodes {} time {} pv {}", depth, best_move.score, self.nodes, self.timer.get_time_passed_millis(), best_move.bit_move.to_uci_string());
+        let mut best_move = self.best_move(position, depth);
+        self.modify_best_move_if_empty(position, &mut best_move);
+        self.pv = self.print_info_depth(best_move, depth).pv;
         best_move
     }
 
     #[inline(always)]
-    fn should_end_search_early(&self) -> bool {
-        if let Some(time) = self.stop_time.as_ref() {
-            return self.timer.get_time_passed_millis() * AVERAGE_BRANCHING_FACTOR as u128 > *time;
+    fn should_stop_iterating(&self) -> bool {
+        self.time_budget_start()
+            .is_some_and(|start_time| self.time_manager.should_stop_iterating(self.timer.get_time_passed_millis() - start_time))
+    }
+
+    #[inline(always)]
+    fn found_mate_within_limit(&self, score: Score) -> bool {
+        self.limits.mate.is_some_and(|mate| score.is_checkmate() && score.is_positive() && score.moves_to_mate() <= mate as i16)
+    }
+
+    // NOTE: In infinite and ponder mode, the best move must not be reported before the search
+    // is stopped or the ponderhit arrives
+    #[inline(always)]
+    fn wait_for_stop_if_infinite(&self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        while (self.limits.infinite || self.is_pondering()) && !self.should_stop_calculating() {
+            std::thread::sleep(std::time::Duration::from_millis(INFINITE_SEARCH_POLL_MS));
         }
-        false
     }
 
     fn modify_best_move_if_empty(&self, position: &Position, best_move: &mut ScoringMove) {
@@ -430,14 +795,10 @@ impl Search {
         let mut best_move = ScoringMove::blank(Score::BLANK);
 
         for current_depth in 1..=depth {
-            self.nodes = 0;
-            let new_best_move = self.best_move(position, current_depth);
+            let new_best_move = self.iteration_best_move(position, current_depth, best_move);
 
             if self.should_stop_calculating() {
-                #[cfg(feature = "tt")]
-                TranspositionTable::reset();
-
-                uci_println!(self, "info string ended iterative search and reset transposition table");
+                uci_println!(self, "info string ended iterative search");
                 break;
             }
 
@@ -449,12 +810,16 @@ impl Search {
             }
 
             best_move = new_best_move;
-            let found_mate = new_best_move.score.is_checkmate();
+            self.pv = self.print_info_depth(new_best_move, current_depth).pv;
+            self.time_manager.update(best_move);
 
-            self.print_info_depth(position, new_best_move, current_depth, found_mate);
+            if self.found_mate_within_limit(best_move.score) {
+                uci_println!(self, "info string ended iterative search after finding mate within limit");
+                break;
+            }
 
-            if self.should_end_search_early() {
-                uci_println!(self, "info string ended iterative search early based on time prediction");
+            if self.should_stop_iterating() {
+                uci_println!(self, "info string ended iterative search early based on time management");
                 break;
             }
         }
@@ -464,29 +829,109 @@ impl Search {
     }
 
     #[inline(always)]
-    fn print_info_depth(&self, position: &Position, scoring_move: ScoringMove, current_depth: usize, found_mate: bool) {
+    fn go_iterative_deepening_multi_pv(&mut self, position: &Position, depth: usize, num_lines: usize) -> Vec<PvLine> {
+        let mut pv_lines = Vec::new();
+
+        for current_depth in 1..=depth {
+            let mut new_pv_lines: Vec<PvLine> = Vec::new();
+
+            // Each line is found by searching the root again, excluding the best moves of previous lines
+            for _ in 0..num_lines {
+                let new_best_move = self.best_move(position, current_depth);
+
+                if self.should_stop_calculating() || new_best_move.bit_move == BitMove::EMPTY {
+                    break;
+                }
+
+                self.excluded_root_moves.push(new_best_move.bit_move);
+                new_pv_lines.push(PvLine::new(new_best_move, self.get_pv(new_best_move.bit_move)));
+            }
+
+            self.excluded_root_moves.clear();
+
+            if self.should_stop_calculating() {
+                uci_println!(self, "info string ended iterative search");
+                break;
+            }
+
+            if new_pv_lines.is_empty() {
+                uci_println!(self, "info string found no lines at depth {current_depth}");
+                continue;
+            }
+
+            new_pv_lines.sort_by_key(|pv_line| std::cmp::Reverse(pv_line.scoring_move));
+
+            for (line_index, pv_line) in new_pv_lines.iter().enumerate() {
+                self.print_info_pv_line(pv_line, current_depth, Some(line_index + 1));
+            }
+
+            pv_lines = new_pv_lines;
+            self.pv = pv_lines[0].pv.clone();
+            self.update_best_move_found(pv_lines[0].scoring_move.bit_move);
+            self.time_manager.update(pv_lines[0].scoring_move);
+
+            if self.found_mate_within_limit(pv_lines[0].scoring_move.score) {
+                uci_println!(self, "info string ended iterative search after finding mate within limit");
+                break;
+            }
+
+            if self.should_stop_iterating() {
+                uci_println!(self, "info string ended iterative search early based on time management");
+                break;
+            }
+        }
+
+        if pv_lines.is_empty() {
+            let mut best_move = ScoringMove::blank(Score::BLANK);
+            self.modify_best_move_if_empty(position, &mut best_move);
+            pv_lines.push(PvLine::new(best_move, vec![best_move.bit_move]));
+        }
+
+        pv_lines
+    }
+
+    #[inline(always)]
+    fn print_info_depth(&self, scoring_move: ScoringMove, current_depth: usize) -> PvLine {
+        self.update_best_move_found(scoring_move.bit_move);
+        let pv_line = PvLine::new(scoring_move, self.get_pv(scoring_move.bit_move));
+        self.print_info_pv_line(&pv_line, current_depth, None);
+        pv_line
+    }
+
+    #[inline(always)]
+    fn print_info_pv_line(&self, pv_line: &PvLine, current_depth: usize, multi_pv_index: Option<usize>) {
+        uci_print!(self, "info depth {current_depth:<2} ");
+
+        if let Some(multi_pv_index) = multi_pv_index {
+            uci_print!(self, "multipv {multi_pv_index:<2} ");
+        }
+
         uci_println!(self, 
-            "info depth {:<2} score {:<10} nodes {:<10} time {:<6} pv {}",
-            current_depth,
-            Self::score_or_mate_string(scoring_move.score, found_mate),
+            "score {:<10} nodes {:<10} time {:<6} pv {}",
+            Self::score_or_mate_string(pv_line.scoring_move.score, pv_line.scoring_move.score.is_checkmate()),
             self.nodes,
             self.timer.get_time_passed_millis(),
-            self.get_pv(position, current_depth, scoring_move.bit_move),
+            pv_line.to_uci_string(),
         );
     }
 
     #[inline(always)]
     #[cfg(feature = "lazy_smp")]
     fn go_lazy_smp(&mut self, position: &Position, depth: usize) -> ScoringMove {
-        let best_move = Arc::new(Mutex::new(ScoringMove::blank(Score::BLANK)));
+        let best_line = Arc::new(Mutex::new(PvLine::new(ScoringMove::blank(Score::BLANK), Vec::new())));
         let ended_early = Arc::new(AtomicBool::new(false));
 
-        crate::GlobalThreadPool::get()
+        // NOTE: The time manager is shared with the depth it was last updated at, since threads
+        // finish their iterations out of order and each one searches a clone of the search
+        let time_manager = Arc::new(Mutex::new((self.time_manager.clone(), 0)));
+
+        self.context.thread_pool.get()
             .scope(|s| {
             for current_depth in 1..=depth {
                 let mut self_ref = self.clone();
-                let best_move = best_move.clone();
+                let best_line = best_line.clone();
                 let ended_early = ended_early.clone();
+                let time_manager = time_manager.clone();
 
                 s.spawn(move |_| {
                     if self_ref.should_stop_calculating() {
@@ -506,19 +951,30 @@ impl Search {
                     // NOTE: This prevents a bug where concurrent threads overwrite an already
                     // existing mating line and also help return the search early if a mate has
                     // already been found.
-                    if let Ok(mut best_move) = best_move.lock() {
-                        if !best_move.score.is_checkmate() {
-                            *best_move = new_best_move;
-                        } else {
+                    if let Ok(best_line) = best_line.lock() {
+                        if best_line.scoring_move.score.is_checkmate() {
                             return;
                         }
                     }
 
-                    let found_mate = new_best_move.score.is_checkmate();
-        
-                    self_ref.print_info_depth(position, new_best_move, current_depth, found_mate);
+                    let new_best_line = self_ref.print_info_depth(new_best_move, current_depth);
+
+                    if let Ok(mut best_line) = best_line.lock() {
+                        if !best_line.scoring_move.score.is_checkmate() {
+                            *best_line = new_best_line;
+                        }
+                    }
+
+                    if let Ok(mut time_manager) = time_manager.lock() {
+                        let (shared_time_manager, updated_depth) = &mut *time_manager;
+                        if current_depth > *updated_depth {
+                            shared_time_manager.update(new_best_move);
+                            *updated_depth = current_depth;
+                        }
+                        self_ref.time_manager = shared_time_manager.clone();
+                    }
 
-                    if self_ref.should_end_search_early() {
+                    if self_ref.should_stop_iterating() {
                         self_ref.begin_stop_calculating();
                         ended_early.store(true, Ordering::Relaxed);
                     }
@@ -527,24 +983,29 @@ impl Search {
         });
 
         if self.should_stop_calculating() {
-            uci_print!(self, "info string ended iterative search and reset transposition table");
+            uci_print!(self, "info string ended iterative search");
             if ended_early.load(Ordering::Relaxed) {
-                uci_println!(self, " based on time prediction");
+                uci_println!(self, " based on time management");
             } else {
                 uci_println!(self, );
             }
-            TranspositionTable::reset();
         }
 
-        self.modify_best_move_if_empty(position, &mut best_move.lock().unwrap());
-        let best_move = best_move.lock().unwrap();
-        *best_move
+        let mut best_line = best_line.lock().unwrap();
+        self.modify_best_move_if_empty(position, &mut best_line.scoring_move);
+        self.pv = best_line.pv.clone();
+        best_line.scoring_move
+    }
+
+    // NOTE: Tablebase wins are scored below the mate range, so they're reported in centipawns
+    #[cfg(feature = "syzygy_tablebase")]
+    fn tablebase_info_string(&self, score: Score) -> String {
+        format!("info score {} time {}", Self::score_or_mate_string(score, score.is_checkmate()), self.timer.get_time_passed_millis())
     }
 
     fn score_or_mate_string(score: Score, found_mate: bool) -> String {
         if found_mate {
-            // format!("mate {}", ((f32::from(Score::CHECKMATE - score.abs())) / 2.0).ceil() as i16 * i16::from(score.signum()))
-            format!("cp {score}")
+            format!("mate {}", score.moves_to_mate())
         } else {
             format!("cp {score}")
         }
@@ -552,12 +1013,22 @@ impl Search {
 
     #[inline(always)]
     pub fn go(&mut self, position: &Position, depth: Option<usize>, stop_time: Option<u128>) -> ScoringMove {
-        self.reset(stop_time);
+        self.go_with_limits(position, SearchLimits::new(depth, stop_time))
+    }
+
+    pub fn go_with_limits(&mut self, position: &Position, limits: SearchLimits) -> ScoringMove {
+        #[cfg(any(feature = "opening_book", feature = "syzygy_tablebase"))]
+        let is_restricted = limits.is_restricted() || self.multi_pv > 1;
+        let limits = self.skill.limit(limits);
+        let depth = limits.depth;
+        self.reset(limits);
+        #[cfg(any(feature = "opening_book", feature = "syzygy_tablebase"))]
+        let stop_time = self.time_manager.hard_limit();
 
         #[cfg(feature = "opening_book")]
-        if self.in_opening && stop_time.is_none_or(|time| time >= OPENING_BOOK_SEARCH_THRESHOLD) {
+        if self.own_book && self.in_opening && !is_restricted && stop_time.is_none_or(|time| time >= OPENING_BOOK_SEARCH_THRESHOLD) {
             uci_println!(self, "info string searching for opening move");
-            if let Some(opening_move) = self.opening_book.get_move(position) {
+            if let Some(opening_move) = self.opening_book.get_move(position, self.best_book_move) {
                 uci_println!(self, "info time {}", self.timer.get_time_passed_millis());
                 uci_println!(self, "bestmove {}", opening_move.to_uci_string());
                 return ScoringMove::from(opening_move);
@@ -569,7 +1040,7 @@ impl Search {
         }
 
         #[cfg(feature = "syzygy_tablebase")]
-        if let Some(tablebase) = self.tablebase.as_ref() {
+        if let Some(tablebase) = self.tablebase.as_ref().as_ref().filter(|_| !is_restricted) {
             let tablebase_max_pieces_u8 = tablebase.get_max_pieces() as u8;
             if position.all_occupancy.count_bits() <= tablebase_max_pieces_u8 + 1 {
                 uci_println!(self, "info string searching for tablebase move");
@@ -586,7 +1057,7 @@ impl Search {
                         if new_position.apply_pseudo_legal_move(scoring_move.bit_move) {
                             if let Some(tablebase_move) = tablebase.best_move(&new_position) {
                                 if tablebase_move.score.is_negative() {
-                                    best_move_option = Some(ScoringMove::new(scoring_move.bit_move, Score::CHECKMATE));
+                                    best_move_option = Some(ScoringMove::new(scoring_move.bit_move, -tablebase_move.score));
                                     break;
                                 }
                             } else {
@@ -597,11 +1068,7 @@ impl Search {
                 }
 
                 if let Some(best_move) = best_move_option {
-                    uci_println!(self, 
-                        "info score {} time {}",
-                        Self::score_or_mate_string(best_move.score, true),
-                        self.timer.get_time_passed_millis(),
-                    );
+                    uci_println!(self, "{}", self.tablebase_info_string(best_move.score));
                     uci_println!(self, "bestmove {}", best_move.bit_move.to_uci_string());
                     return best_move;
                 } else {
@@ -610,26 +1077,86 @@ impl Search {
             }
         }
 
-        let best_move = self.go_search(position, depth, stop_time);
-        uci_println!(self, "bestmove {}", best_move.bit_move.to_uci_string());
+        let best_move = self.go_search(position, depth);
+        self.wait_for_stop_if_infinite();
+        self.print_best_move(best_move.bit_move);
         best_move
     }
 
+    fn print_best_move(&self, best_move: BitMove) {
+        uci_print!(self, "bestmove {}", best_move.to_uci_string());
+
+        if self.show_ponder_move {
+            if let Some(ponder_move) = self.ponder_move(best_move) {
+                uci_print!(self, " ponder {}", ponder_move.to_uci_string());
+            }
+        }
+
+        uci_println!(self);
+    }
+
+    // NOTE: The ponder move is the expected reply, which is the second move of the pv
+    fn ponder_move(&self, best_move: BitMove) -> Option<BitMove> {
+        if self.pv.first() == Some(&best_move) {
+            self.pv.get(1).copied()
+        } else {
+            None
+        }
+    }
+
+    // NOTE: Unlike go, this always performs a search, since opening books and tablebases only
+    // provide a single move.
+    pub fn go_multi_pv(&mut self, position: &Position, depth: Option<usize>, stop_time: Option<u128>) -> Vec<PvLine> {
+        self.reset(SearchLimits::new(depth, stop_time));
+        let pv_lines = self.go_iterative_deepening_multi_pv(position, depth.unwrap_or(MAX_DEPTH), self.multi_pv);
+        self.print_best_move(pv_lines[0].scoring_move.bit_move);
+        pv_lines
+    }
+
     #[inline(always)]
-    fn go_search(&mut self, position: &Position, depth: Option<usize>, stop_time: Option<u128>) -> ScoringMove {
+    fn go_search(&mut self, position: &Position, depth: Option<usize>) -> ScoringMove {
         uci_print!(self, "info string searching for best move");
 
-        if let Some(stop_time) = stop_time {
-            uci_print!(self, " within {stop_time} milliseconds");
+        if let (Some(soft_limit), Some(hard_limit)) = (self.time_manager.soft_limit(), self.time_manager.hard_limit()) {
+            uci_print!(self, " within {soft_limit} to {hard_limit} milliseconds");
+
+            if self.limits.ponder {
+                uci_print!(self, " after ponderhit");
+            }
         }
 
         if let Some(depth) = depth {
             uci_print!(self, " with a maximum depth of {depth}");
         }
 
+        if let Some(nodes) = self.limits.nodes {
+            uci_print!(self, " with a maximum of {nodes} nodes");
+        }
+
+        if let Some(mate) = self.limits.mate {
+            uci_print!(self, " for a mate in {mate}");
+        }
+
         uci_println!(self);
 
-        let depth = depth.unwrap_or(MAX_DEPTH);
+        // NOTE: A mate in n moves is always found within 2n plies
+        let depth = depth
+            .or(self.limits.mate.map(|mate| 2 * mate))
+            .unwrap_or(MAX_DEPTH)
+            .min(MAX_DEPTH);
+
+        if MoveGeneration::generate_moves::<BitMove, Legal>(position).len() == 1 {
+            self.time_manager.set_only_move();
+        }
+
+        if self.skill.is_limited() {
+            let pv_lines = self.go_iterative_deepening_multi_pv(position, depth, self.multi_pv.max(crate::skill::SKILL_MULTI_PV));
+            return self.skill.pick_move(&pv_lines);
+        }
+
+        if self.multi_pv > 1 {
+            return self.go_iterative_deepening_multi_pv(position, depth, self.multi_pv)[0].scoring_move;
+        }
 
         #[cfg(not(feature = "iterative_deepening"))]
         { return self.go_no_iterative_deepening(position, depth); }
@@ -637,45 +1164,56 @@ impl Search {
         #[cfg(not(feature = "lazy_smp"))]
         { return self.go_iterative_deepening(position, depth); }
 
+        // NOTE: Node limited searches are kept on a single thread to make them reproducible
         #[cfg(feature = "lazy_smp")]
-        if crate::GlobalThreadPool::should_parallelize() {
+        if self.context.thread_pool.should_parallelize() && self.limits.nodes.is_none() {
             self.go_lazy_smp(position, depth)
         } else {
             self.go_iterative_deepening(position, depth)
         }
     }
 
-    #[inline(always)]
-    pub fn calculate_stop_time(total_time: Option<u128>, increment_time: Option<u128>) -> Option<u128> {
-        total_time.map(|total_time| total_time / AVERAGE_AMOUNT_OF_MOVES as u128 + increment_time.unwrap_or(0))
+    // NOTE: The pv table can be cut short by a transposition table hit at the root, in which
+    // case only the best move is known.
+    fn get_pv(&self, best_move: BitMove) -> Vec<BitMove> {
+        let root_pv = self.pv_table.get_root_pv();
+        if root_pv.first() == Some(&best_move) {
+            root_pv.to_vec()
+        } else {
+            vec![best_move]
+        }
     }
 
-    fn get_pv(&self, position: &Position, depth: usize, _best_move: BitMove) -> String {
-        #[cfg(feature = "tt")]
-        return self.get_pv_from_tt(position, depth);
+    #[cfg(feature = "opening_book")]
+    #[inline(always)]
+    pub fn set_own_book(&mut self, own_book: bool) {
+        self.own_book = own_book;
+    }
 
-        #[cfg(not(feature = "tt"))]
-        return _best_move.to_uci_string()
+    #[cfg(feature = "opening_book")]
+    #[inline(always)]
+    pub fn set_best_book_move(&mut self, best_book_move: bool) {
+        self.best_book_move = best_book_move;
     }
 
-    // NOTE: There is a notable chance the pv will be ended early in case a different position
-    // happens to have the same table index. The probability scales inversely with the
-    // size of the transposition table.
-    #[cfg(feature = "tt")]
-    fn get_pv_from_tt(&self, position: &Position, depth: usize) -> String {
-        let mut pv_moves = Vec::new();
-        let mut position_copy = position.clone();
-        for _ in 0..depth {
-            if let Some(tt_entry) = TranspositionTable::probe(position_copy.zobrist_key) {
-                let best_move = tt_entry.best_move;
-                if best_move.bit_move == BitMove::EMPTY {
-                    break;
-                }
-                pv_moves.push(best_move.bit_move.to_uci_string());
-                position_copy.make_move(best_move.bit_move);
-            }
+    // NOTE: An empty path switches back to the Lichess backend, while a book that can't be
+    // loaded leaves the current one in place
+    #[cfg(feature = "opening_book")]
+    pub fn set_book_file(&mut self, path: &str) {
+        if path.is_empty() {
+            self.opening_book = Arc::new(crate::OpeningBook::default());
+            uci_println!(self, "info string using lichess opening book");
+            return;
+        }
+
+        match crate::OpeningBook::from_file(path) {
+            Ok(opening_book) => {
+                self.opening_book = Arc::new(opening_book);
+                self.in_opening = true;
+                uci_println!(self, "info string loaded opening book successfully");
+            },
+            Err(error) => uci_println!(self, "info string error loading opening book: {error}"),
         }
-        pv_moves.join(" ")
     }
 
     #[cfg(feature = "syzygy_tablebase")]
@@ -691,8 +1229,221 @@ impl Search {
 
 #[cfg(test)]
 mod tests {
+    use crate::{FenString, Legal, MoveGeneration, Uci};
+
     use super::*;
 
+    #[test]
+    fn score_or_mate_string_reports_mate_in_moves() {
+        assert_eq!(Search::score_or_mate_string(Score::checkmate_minus_depth(5), true), "mate 3");
+        assert_eq!(Search::score_or_mate_string(-Score::checkmate_minus_depth(4), true), "mate -2");
+        assert_eq!(Search::score_or_mate_string(Score::from(42), false), "cp 42");
+    }
+
+    #[test]
+    #[cfg(feature = "syzygy_tablebase")]
+    fn tablebase_wins_are_reported_in_centipawns() {
+        let search = Search::default();
+        assert!(search.tablebase_info_string(Score::TABLEBASE_WIN - 12).starts_with("info score cp 8988 time "));
+        assert!(search.tablebase_info_string(-Score::TABLEBASE_WIN + 12).starts_with("info score cp -8988 time "));
+    }
+
+    #[test]
+    #[cfg(feature = "negamax")]
+    fn go_finds_mate_in_one() {
+        let position = FenString::from("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").parse().unwrap();
+        let best_move = Search::default().go(&position, Some(3), None);
+        assert_eq!(best_move.bit_move.to_uci_string(), "a1a8");
+        assert_eq!(best_move.score.moves_to_mate(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "aspiration_windows")]
+    fn aspiration_window_widens_until_best_move_is_found() {
+        let position: Position = FenString::kiwipete().parse().unwrap();
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&position);
+        let mut search = Search { in_opening: false, ..Default::default() };
+        for previous_score in [-600, 0, 600] {
+            search.reset(SearchLimits::default());
+            let best_move = search.aspiration_window_best_move(&position, 4, Score::from(previous_score));
+            assert!(legal_moves.contains(&best_move.bit_move));
+        }
+    }
+
+    #[test]
+    fn go_multi_pv_returns_distinct_ranked_lines() {
+        let mut search = Search::default();
+        search.set_multi_pv(3);
+        let pv_lines = search.go_multi_pv(&Position::starting_position(), Some(3), None);
+        assert_eq!(pv_lines.len(), 3);
+        assert!(pv_lines.windows(2).all(|lines| lines[0].scoring_move.score >= lines[1].scoring_move.score));
+        assert!(pv_lines.iter().all(|line| line.pv.first() == Some(&line.scoring_move.bit_move)));
+        assert_ne!(pv_lines[0].scoring_move.bit_move, pv_lines[1].scoring_move.bit_move);
+        assert_ne!(pv_lines[1].scoring_move.bit_move, pv_lines[2].scoring_move.bit_move);
+        assert_ne!(pv_lines[0].scoring_move.bit_move, pv_lines[2].scoring_move.bit_move);
+    }
+
+    #[test]
+    fn limited_skill_plays_legal_moves_within_node_cap() {
+        let position = Position::starting_position();
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&position);
+        let mut search = Search { in_opening: false, ..Default::default() };
+        search.set_elo(600);
+        for _ in 0..5 {
+            let best_move = search.go(&position, Some(10), None);
+            assert!(legal_moves.contains(&best_move.bit_move));
+            assert!(search.nodes <= 2 * search.skill.max_nodes());
+        }
+    }
+
+    #[test]
+    fn go_with_search_moves_only_considers_given_moves() {
+        let position = Position::starting_position();
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&position);
+        let search_move = Uci::parse_move_string(&legal_moves, "a2a3").unwrap();
+        let limits = SearchLimits {
+            depth: Some(3),
+            search_moves: vec![search_move],
+            ..Default::default()
+        };
+        assert_eq!(Search::default().go_with_limits(&position, limits).bit_move, search_move);
+    }
+
+    #[test]
+    fn go_with_node_limit_stops_search() {
+        let mut search = Search::default();
+        let limits = SearchLimits {
+            nodes: Some(1000),
+            ..Default::default()
+        };
+        let best_move = search.go_with_limits(&Position::starting_position(), limits);
+        assert_ne!(best_move.bit_move, BitMove::EMPTY);
+        // NOTE: Unwinding the search visits a few more nodes after the limit is reached
+        assert!(search.nodes < 2 * 1000);
+    }
+
+    #[test]
+    #[cfg(feature = "tt")]
+    fn aborted_search_keeps_transposition_table() {
+        let position: Position = FenString::kiwipete().parse().unwrap();
+        let mut search = Search::default();
+        search.go(&position, Some(2), None);
+        let tt_entry = search.context.transposition_table.probe(position.zobrist_key).unwrap();
+        assert_eq!(Some(&tt_entry.best_move.bit_move), search.pv.first());
+
+        let limits = SearchLimits {
+            nodes: Some(5000),
+            ..Default::default()
+        };
+        search.go_with_limits(&position, limits);
+        assert!(search.should_stop_calculating());
+        assert!(search.context.transposition_table.probe(position.zobrist_key).is_some_and(|tt_entry| (2..MAX_DEPTH as u16).contains(&tt_entry.depth)));
+
+        search.clear_transposition_table();
+        assert!(search.context.transposition_table.probe(position.zobrist_key).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "negamax")]
+    fn go_with_mate_limit_finds_mate() {
+        let position = FenString::from("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").parse().unwrap();
+        let limits = SearchLimits {
+            mate: Some(1),
+            ..Default::default()
+        };
+        let best_move = Search::default().go_with_limits(&position, limits);
+        assert_eq!(best_move.bit_move.to_uci_string(), "a1a8");
+        assert_eq!(best_move.score.moves_to_mate(), 1);
+    }
+
+    #[test]
+    fn ponder_move_is_legal_reply_to_best_move() {
+        let position = Position::starting_position();
+        let mut search = Search { in_opening: false, ..Default::default() };
+        let best_move = search.go(&position, Some(3), None).bit_move;
+        assert_ne!(best_move, BitMove::EMPTY);
+        assert_eq!(search.pv.first(), Some(&best_move));
+        let ponder_move = search.ponder_move(best_move).unwrap();
+        let mut position_copy = position.clone();
+        position_copy.make_move(best_move);
+        assert!(MoveGeneration::generate_moves::<BitMove, Legal>(&position_copy).iter().any(|&m| m == ponder_move));
+    }
+
+    #[test]
+    fn go_ponder_waits_for_ponderhit() {
+        let mut search = Search::default();
+        let ponder_hit = search.get_ponder_hit();
+        let limits = SearchLimits {
+            depth: Some(2),
+            stop_time: Some(10),
+            ponder: true,
+            ..Default::default()
+        };
+        let handle = std::thread::spawn(move || search.go_with_limits(&Position::starting_position(), limits));
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!handle.is_finished());
+        ponder_hit.store(true, Ordering::Relaxed);
+        assert_ne!(handle.join().unwrap().bit_move, BitMove::EMPTY);
+    }
+
+    fn assert_legal_pv(position: &Position, pv: &[BitMove]) {
+        let mut position_copy = position.clone();
+        for &bit_move in pv {
+            let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&position_copy);
+            assert!(legal_moves.iter().any(|&legal_move| legal_move == bit_move), "illegal pv move {}", bit_move.to_uci_string());
+            position_copy.make_move(bit_move);
+        }
+    }
+
+    #[test]
+    fn reported_pvs_are_legal_move_sequences() {
+        for fen_string in [FenString::startpos(), FenString::rook()] {
+            let position = fen_string.parse().unwrap();
+            let mut search = Search {
+                in_opening: false,
+                ..Default::default()
+            };
+            for depth in 1..=3 {
+                let best_move = search.go(&position, Some(depth), None);
+                assert_eq!(search.pv.first(), Some(&best_move.bit_move));
+                assert_legal_pv(&position, &search.pv);
+            }
+
+            search.set_multi_pv(2);
+            for pv_line in search.go_multi_pv(&position, Some(3), None) {
+                assert_legal_pv(&position, &pv_line.pv);
+            }
+        }
+    }
+
+    fn isolated_search_result(search: &mut Search, position: &Position) -> (BitMove, u64) {
+        search.in_opening = false;
+        let best_move = search.go(position, Some(4), None);
+        (best_move.bit_move, search.nodes)
+    }
+
+    #[test]
+    fn independent_searches_are_isolated_and_deterministic() {
+        let position: Position = FenString::startpos().parse().unwrap();
+        let other_position: Position = FenString::rook().parse().unwrap();
+        let expected_result = isolated_search_result(&mut Search::default(), &position);
+
+        let mut search = Search::default();
+        let mut other_search = Search::default();
+        isolated_search_result(&mut other_search, &other_position);
+        assert_eq!(isolated_search_result(&mut search, &position), expected_result);
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let position = position.clone();
+                std::thread::spawn(move || isolated_search_result(&mut Search::default(), &position))
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected_result);
+        }
+    }
+
     #[test]
     fn go_returns_non_empty_move() {
         assert_ne!(
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use crate::{HistoryHeuristic, KillerMoves, TranspositionTable};
+
+// NOTE: The transposition table and thread pool are shared between clones of the context, which
+// is what lets lazy smp threads cooperate, while move ordering heuristics are owned by each clone.
+// Separately created contexts never share any state.
+#[derive(Clone, Default)]
+pub(crate) struct SearchContext {
+    pub(crate) transposition_table: Arc<TranspositionTable>,
+    pub(crate) killer_moves: KillerMoves,
+    pub(crate) history_heuristic: HistoryHeuristic,
+
+    #[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
+    pub(crate) thread_pool: crate::ThreadPool,
+}
+
+impl SearchContext {
+    pub(crate) fn reset(&mut self) {
+        self.clear_transposition_table();
+        self.killer_moves.reset();
+        self.history_heuristic.reset();
+    }
+
+    // NOTE: The table is cleared in parallel on the search's own pool rather than the global one
+    pub(crate) fn clear_transposition_table(&self) {
+        #[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
+        self.thread_pool.install(|| self.transposition_table.reset());
+
+        #[cfg(not(any(feature = "parallel_perft", feature = "lazy_smp")))]
+        self.transposition_table.reset();
+    }
+
+    pub(crate) fn resize_transposition_table(&mut self, size_mb: usize) {
+        self.transposition_table = Arc::new(TranspositionTable::new(size_mb));
+    }
+
+    #[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
+    pub(crate) fn set_num_threads(&mut self, num_threads: usize) {
+        self.thread_pool = crate::ThreadPool::new(num_threads);
+    }
+}
@@ -0,0 +1,29 @@
+use crate::BitMove;
+
+#[derive(Clone, Default, Debug)]
+pub struct SearchLimits {
+    pub depth: Option<usize>,
+    pub stop_time: Option<u128>,
+    pub total_time: Option<u128>,
+    pub increment_time: Option<u128>,
+    pub moves_to_go: Option<u32>,
+    pub nodes: Option<u64>,
+    pub mate: Option<usize>,
+    pub search_moves: Vec<BitMove>,
+    pub infinite: bool,
+    pub ponder: bool,
+}
+
+impl SearchLimits {
+    #[inline(always)]
+    pub fn new(depth: Option<usize>, stop_time: Option<u128>) -> SearchLimits {
+        SearchLimits { depth, stop_time, ..Default::default() }
+    }
+
+    // NOTE: Restricted searches can't be answered by the opening book or tablebase,
+    // since they wouldn't respect the limits given.
+    #[inline(always)]
+    pub(crate) fn is_restricted(&self) -> bool {
+        self.nodes.is_some() || self.mate.is_some() || !self.search_moves.is_empty() || self.infinite || self.ponder
+    }
+}
@@ -0,0 +1,132 @@
+use std::hash::{BuildHasher, RandomState};
+
+use crate::{PvLine, RandomNumberGenerator, ScoringMove, SearchLimits};
+
+pub(crate) const MIN_SKILL_LEVEL: u8 = 0;
+pub(crate) const MAX_SKILL_LEVEL: u8 = 20;
+pub(crate) const MIN_ELO: u16 = 600;
+pub(crate) const MAX_ELO: u16 = 2400;
+
+// NOTE: Limited searches pick among this many of the best root moves
+pub(crate) const SKILL_MULTI_PV: usize = 4;
+const MAX_SCORE_DELTA: i32 = 100;
+const NODES_PER_SQUARED_LEVEL: u64 = 1000;
+
+// Weakens the search for skill levels below the maximum by capping its depth and nodes, and by
+// randomly picking one of the best root moves, based on how far its score is from the best one
+#[derive(Clone)]
+pub(crate) struct Skill {
+    level: u8,
+    rng: RandomNumberGenerator,
+}
+
+impl Default for Skill {
+    fn default() -> Self {
+        // NOTE: The seed differs between instances, so games against a limited search vary
+        let seed = RandomState::new().hash_one(0_u8) as u32 | 1;
+        Self { level: MAX_SKILL_LEVEL, rng: RandomNumberGenerator::new(seed) }
+    }
+}
+
+impl Skill {
+    #[inline(always)]
+    pub(crate) fn set_level(&mut self, level: u8) {
+        self.level = level.min(MAX_SKILL_LEVEL);
+    }
+
+    #[inline(always)]
+    pub(crate) fn level_from_elo(elo: u16) -> u8 {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+        ((elo - MIN_ELO) as u32 * MAX_SKILL_LEVEL as u32 / (MAX_ELO - MIN_ELO) as u32) as u8
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_limited(&self) -> bool {
+        self.level < MAX_SKILL_LEVEL
+    }
+
+    #[inline(always)]
+    pub(crate) fn max_depth(&self) -> usize {
+        1 + self.level as usize / 2
+    }
+
+    #[inline(always)]
+    pub(crate) fn max_nodes(&self) -> u64 {
+        NODES_PER_SQUARED_LEVEL * (self.level as u64 + 1).pow(2)
+    }
+
+    pub(crate) fn limit(&self, mut limits: SearchLimits) -> SearchLimits {
+        if self.is_limited() {
+            limits.depth = Some(limits.depth.map_or(self.max_depth(), |depth| depth.min(self.max_depth())));
+            limits.nodes = Some(limits.nodes.map_or(self.max_nodes(), |nodes| nodes.min(self.max_nodes())));
+        }
+
+        limits
+    }
+
+    // NOTE: Each line gets a bonus that is partly random and partly makes up for its worse score,
+    // which is based on the weakness of the skill level:
+    // https://github.com/official-stockfish/Stockfish/blob/master/src/search.cpp
+    pub(crate) fn pick_move(&mut self, pv_lines: &[PvLine]) -> ScoringMove {
+        let top_score = i16::from(pv_lines[0].scoring_move.score) as i32;
+        let last_score = i16::from(pv_lines[pv_lines.len() - 1].scoring_move.score) as i32;
+        let delta = (top_score - last_score).min(MAX_SCORE_DELTA);
+        let weakness = 120 - 2 * self.level as i32;
+
+        let mut best_move = pv_lines[0].scoring_move;
+        let mut max_score = i32::MIN;
+        for pv_line in pv_lines {
+            let score = i16::from(pv_line.scoring_move.score) as i32;
+            let push = (weakness * (top_score - score) + delta * (self.rng.generate_u32() % weakness as u32) as i32) / 128;
+            if score + push >= max_score {
+                max_score = score + push;
+                best_move = pv_line.scoring_move;
+            }
+        }
+
+        best_move
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BitMove, Legal, MoveGeneration, Position, Score};
+
+    use super::*;
+
+    #[test]
+    fn elo_maps_to_skill_levels() {
+        assert_eq!(Skill::level_from_elo(0), MIN_SKILL_LEVEL);
+        assert_eq!(Skill::level_from_elo(MIN_ELO), MIN_SKILL_LEVEL);
+        assert_eq!(Skill::level_from_elo(1500), 10);
+        assert_eq!(Skill::level_from_elo(MAX_ELO), MAX_SKILL_LEVEL);
+    }
+
+    #[test]
+    fn limited_skill_caps_depth_and_nodes() {
+        let mut skill = Skill::default();
+        assert_eq!(skill.limit(SearchLimits::new(Some(8), None)).depth, Some(8));
+
+        skill.set_level(4);
+        let limits = skill.limit(SearchLimits::new(Some(8), None));
+        assert_eq!(limits.depth, Some(3));
+        assert_eq!(limits.nodes, Some(25_000));
+        assert_eq!(skill.limit(SearchLimits::new(Some(2), None)).depth, Some(2));
+    }
+
+    #[test]
+    fn limited_skill_picks_near_best_moves() {
+        let moves = MoveGeneration::generate_moves::<BitMove, Legal>(&Position::starting_position());
+        let pv_lines: Vec<PvLine> = [50, 40, 30, -2000]
+            .into_iter()
+            .zip(moves.iter())
+            .map(|(score, &bit_move)| PvLine::new(ScoringMove::new(bit_move, Score::from(score)), vec![bit_move]))
+            .collect();
+
+        let mut skill = Skill::default();
+        skill.set_level(MIN_SKILL_LEVEL);
+        let picked_moves: Vec<BitMove> = (0..100).map(|_| skill.pick_move(&pv_lines).bit_move).collect();
+        assert!(!picked_moves.contains(&pv_lines[3].scoring_move.bit_move));
+        assert!(picked_moves.iter().any(|&bit_move| bit_move != pv_lines[0].scoring_move.bit_move));
+    }
+}
@@ -29,7 +29,7 @@ impl Square {
 
     #[inline(always)]
     pub fn is_white(self) -> bool {
-        (self as u8 / 8 + self as u8) % 2 == 0
+        (self as u8 / 8 + self as u8).is_multiple_of(2)
     }
 
     #[inline(always)]
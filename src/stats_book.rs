@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use crate::{BitMove, Color, Legal, MoveGeneration, MoveStats, OpeningBookError, PgnGame, PgnReader, PolyglotBook, Position, Uci};
+
+pub(crate) const STATS_BOOK_EXTENSION: &str = ".book";
+const ENTRY_SIZE: usize = 22;
+const DEFAULT_MAX_PLIES: usize = 24;
+
+#[derive(Clone, Copy, Debug)]
+struct StatsEntry {
+    key: u64,
+    raw_move: u16,
+    stats: MoveStats,
+}
+
+// Reads opening books built from PGN files, which consist of the results of the games in which a
+// move was played from a position. Positions are keyed and moves are encoded like in Polyglot
+// books, but each entry holds the white, draw and black counts instead of a weight.
+pub(crate) struct StatsBook {
+    entries: Vec<StatsEntry>,
+}
+
+impl StatsBook {
+    pub(crate) fn from_file(path: &str) -> Result<StatsBook, OpeningBookError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<StatsBook, OpeningBookError> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(OpeningBookError::FileLength(bytes.len()));
+        }
+
+        let read_u32 = |chunk: &[u8], offset: usize| u32::from_be_bytes(chunk[offset..offset + 4].try_into().unwrap());
+        let mut entries: Vec<StatsEntry> = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|chunk| StatsEntry {
+                key: u64::from_be_bytes(chunk[0..8].try_into().unwrap()),
+                raw_move: u16::from_be_bytes(chunk[8..10].try_into().unwrap()),
+                stats: MoveStats {
+                    white: read_u32(chunk, 10),
+                    draws: read_u32(chunk, 14),
+                    black: read_u32(chunk, 18),
+                },
+            })
+            .collect();
+
+        entries.sort_by_key(|entry| entry.key);
+        Ok(StatsBook { entries })
+    }
+
+    pub(crate) fn get_move_stats(&self, position: &Position) -> Vec<(BitMove, MoveStats)> {
+        let key = PolyglotBook::get_key(position);
+        let first_index = self.entries.partition_point(|entry| entry.key < key);
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(position);
+
+        self.entries[first_index..]
+            .iter()
+            .take_while(|entry| entry.key == key)
+            .filter_map(|entry| {
+                let move_string = PolyglotBook::get_move_string(position, entry.raw_move);
+                Uci::parse_move_string(&legal_moves, &move_string)
+                    .ok()
+                    .map(|bit_move| (bit_move, entry.stats))
+            })
+            .collect()
+    }
+}
+
+// Builds a stats book by replaying the opening moves of every finished game in PGN files
+pub struct StatsBookBuilder {
+    max_plies: usize,
+    move_stats: HashMap<(u64, u16), MoveStats>,
+}
+
+impl Default for StatsBookBuilder {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PLIES)
+    }
+}
+
+impl StatsBookBuilder {
+    pub fn new(max_plies: usize) -> StatsBookBuilder {
+        StatsBookBuilder { max_plies, move_stats: HashMap::new() }
+    }
+
+    // NOTE: Games that can't be parsed are skipped. Returns the number of games that were added.
+    pub fn add_pgn(&mut self, pgn: &str) -> usize {
+        PgnReader::new(pgn.as_bytes())
+            .filter_map(Result::ok)
+            .filter(|pgn_game| self.add_game(pgn_game))
+            .count()
+    }
+
+    // NOTE: Games without a result are skipped. Returns whether the game was added.
+    pub fn add_game(&mut self, pgn_game: &PgnGame) -> bool {
+        let winner = match pgn_game.result.as_str() {
+            "1-0" => Some(Color::White),
+            "0-1" => Some(Color::Black),
+            "1/2-1/2" => None,
+            _ => return false,
+        };
+
+        let mut position = pgn_game.start_position.clone();
+        for &bit_move in pgn_game.moves.iter().take(self.max_plies) {
+            let move_stats = self.move_stats
+                .entry((PolyglotBook::get_key(&position), PolyglotBook::encode_move(bit_move)))
+                .or_default();
+
+            match winner {
+                Some(Color::White) => move_stats.white += 1,
+                Some(Color::Black) => move_stats.black += 1,
+                None => move_stats.draws += 1,
+            }
+
+            position.make_move(bit_move);
+        }
+
+        true
+    }
+
+    pub fn num_entries(&self) -> usize {
+        self.move_stats.len()
+    }
+
+    // NOTE: Entries are sorted, so building the same games always results in the same book
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut entries: Vec<(&(u64, u16), &MoveStats)> = self.move_stats.iter().collect();
+        entries.sort_by_key(|&(&key_and_move, _)| key_and_move);
+
+        entries
+            .into_iter()
+            .flat_map(|(&(key, raw_move), move_stats)| {
+                [
+                    key.to_be_bytes().as_slice(),
+                    &raw_move.to_be_bytes(),
+                    &move_stats.white.to_be_bytes(),
+                    &move_stats.draws.to_be_bytes(),
+                    &move_stats.black.to_be_bytes(),
+                ].concat()
+            })
+            .collect()
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), OpeningBookError> {
+        Ok(std::fs::write(path, self.to_bytes())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FenString;
+
+    use super::*;
+
+    const PGN: &str = r#"[Event "Test"]
+[Result "1-0"]
+
+1.e4 {The king's pawn} e5 2.Nf3 (2.f4 exf4) Nc6 3.Bb5 a6 $1 1-0
+
+[Event "Test"]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Nf3 Nf6 ; Petrov
+3. Nxe5 d6 1/2-1/2
+
+[Event "Test"]
+[Result "*"]
+
+1.d4 d5 *
+
+[Event "Test"]
+[Result "0-1"]
+
+1.d4 Nf6 2.c4 e6 0-1
+"#;
+
+    fn move_stats(book: &StatsBook, position: &Position, move_string: &str) -> Option<MoveStats> {
+        book.get_move_stats(position)
+            .into_iter()
+            .find(|(bit_move, _)| bit_move.to_uci_string() == move_string)
+            .map(|(_, move_stats)| move_stats)
+    }
+
+    #[test]
+    fn stats_book_aggregates_game_results() {
+        let mut builder = StatsBookBuilder::default();
+        assert_eq!(builder.add_pgn(PGN), 3);
+        let book = StatsBook::from_bytes(&builder.to_bytes()).unwrap();
+
+        let position = Position::starting_position();
+        assert_eq!(move_stats(&book, &position, "e2e4"), Some(MoveStats { white: 1, draws: 1, black: 0 }));
+        assert_eq!(move_stats(&book, &position, "d2d4"), Some(MoveStats { white: 0, draws: 0, black: 1 }));
+        assert_eq!(book.get_move_stats(&position).len(), 2);
+
+        let position: Position = FenString::from("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3").parse().unwrap();
+        assert_eq!(move_stats(&book, &position, "f1b5"), Some(MoveStats { white: 1, draws: 0, black: 0 }));
+        assert!(move_stats(&book, &position, "f2f4").is_none());
+        assert!(StatsBook::from_bytes(&builder.to_bytes()[1..]).is_err());
+    }
+
+    #[test]
+    fn stats_book_stops_at_max_plies() {
+        let mut builder = StatsBookBuilder::new(2);
+        builder.add_pgn(PGN);
+        assert_eq!(builder.num_entries(), 4);
+    }
+}
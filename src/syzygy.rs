@@ -26,17 +26,39 @@ impl SyzygyTablebase {
             Some((chess_move, maybe_rounded_dtz)) => {
                 let move_string = chess_move.to_uci(CastlingMode::Standard).to_string();
                 
-                // NOTE: The conditions under which the score should be negated are unclear
-                let mut score = if maybe_rounded_dtz.is_zero() { Score::DRAW } else if maybe_rounded_dtz.is_positive() { -Score::CHECKMATE } else { Score::CHECKMATE };
-                score += maybe_rounded_dtz.ignore_rounding().0 as i16;
+                let score = Self::score_from_dtz(maybe_rounded_dtz.ignore_rounding().0);
                 Some(ScoringMove::new(Uci::parse_move_string(&MoveGeneration::generate_moves::<BitMove, Legal>(position), &move_string).ok()?, score))
             },
             None => None,
         }
     }
 
+    // NOTE: The conditions under which the score should be negated are unclear. Wins are scored
+    // below the mate range, since they're derived from the distance to zeroing rather than the
+    // distance to mate.
+    #[inline(always)]
+    fn score_from_dtz(dtz: i32) -> Score {
+        let mut score = if dtz == 0 { Score::DRAW } else if dtz > 0 { -Score::TABLEBASE_WIN } else { Score::TABLEBASE_WIN };
+        score += dtz as i16;
+        score
+    }
+
     #[inline(always)]
     pub(crate) fn get_max_pieces(&self) -> usize {
         self.shakmaty_tablebase.max_pieces()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tablebase_scores_are_below_the_mate_range() {
+        assert_eq!(SyzygyTablebase::score_from_dtz(0), Score::DRAW);
+        assert_eq!(SyzygyTablebase::score_from_dtz(-3), Score::TABLEBASE_WIN - 3);
+        assert_eq!(SyzygyTablebase::score_from_dtz(3), -Score::TABLEBASE_WIN + 3);
+        assert!(!SyzygyTablebase::score_from_dtz(-1).is_checkmate());
+        assert!(!SyzygyTablebase::score_from_dtz(1).is_checkmate());
+    }
+}
@@ -0,0 +1,40 @@
+use rayon::ThreadPoolBuilder;
+use std::sync::Arc;
+
+const PARALLELIZE_THRESHOLD: usize = 3;
+const DEFAULT_NUM_THREADS: usize = 1;
+
+#[derive(Clone)]
+pub(crate) struct ThreadPool {
+    pool: Arc<rayon::ThreadPool>,
+}
+
+impl Default for ThreadPool {
+    fn default() -> ThreadPool {
+        ThreadPool::new(DEFAULT_NUM_THREADS)
+    }
+}
+
+impl ThreadPool {
+    pub(crate) fn new(num_threads: usize) -> ThreadPool {
+        ThreadPool { pool: Arc::new(ThreadPoolBuilder::new().num_threads(num_threads).build().unwrap()) }
+    }
+
+    pub(crate) fn get(&self) -> Arc<rayon::ThreadPool> {
+        self.pool.clone()
+    }
+
+    pub(crate) fn should_parallelize(&self) -> bool {
+        self.pool.current_num_threads() >= PARALLELIZE_THRESHOLD
+    }
+
+    // NOTE: Work that isn't tied to a specific pool, like perft, parallelizes based on the pool
+    // it is currently installed in.
+    pub(crate) fn should_parallelize_current() -> bool {
+        rayon::current_num_threads() >= PARALLELIZE_THRESHOLD
+    }
+
+    pub(crate) fn install<R: Send>(&self, operation: impl FnOnce() -> R + Send) -> R {
+        self.pool.install(operation)
+    }
+}
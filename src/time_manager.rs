@@ -0,0 +1,209 @@
+use crate::{BitMove, Score, ScoringMove, SearchLimits};
+
+const DEFAULT_MOVES_TO_GO: u128 = 25;
+const HARD_LIMIT_FACTOR: u128 = 4;
+const MAX_TIME_USAGE_PERCENT: u128 = 75;
+const BEST_MOVE_CHANGE_EXTENSION_PERCENT: u128 = 150;
+const SCORE_DROP_EXTENSION_PERCENT: u128 = 130;
+const STABLE_BEST_MOVE_REDUCTION_PERCENT: u128 = 60;
+const STABLE_BEST_MOVE_ITERATIONS: usize = 4;
+const SCORE_DROP_THRESHOLD: i16 = 30;
+pub(crate) const DEFAULT_MOVE_OVERHEAD_MS: u128 = 10;
+
+// NOTE: The soft limit decides whether another iteration should be started and is scaled
+// depending on how stable the search is, while the hard limit aborts the search outright.
+#[derive(Clone, Debug)]
+pub struct TimeManager {
+    soft_limit: Option<u128>,
+    hard_limit: Option<u128>,
+    soft_limit_percent: u128,
+    previous_best_move: Option<ScoringMove>,
+    stable_iterations: usize,
+    is_only_move: bool,
+}
+
+impl Default for TimeManager {
+    fn default() -> TimeManager {
+        TimeManager {
+            soft_limit: None,
+            hard_limit: None,
+            soft_limit_percent: 100,
+            previous_best_move: None,
+            stable_iterations: 0,
+            is_only_move: false,
+        }
+    }
+}
+
+impl TimeManager {
+    pub fn new(limits: &SearchLimits, move_overhead: u128) -> TimeManager {
+        if limits.infinite {
+            return TimeManager::default();
+        }
+
+        if let Some(move_time) = limits.stop_time {
+            let limit = move_time.saturating_sub(move_overhead).max(1);
+            return TimeManager::from_limits(limit, limit);
+        }
+
+        match limits.total_time {
+            Some(total_time) => {
+                let available_time = total_time.saturating_sub(move_overhead).max(1);
+                let moves_to_go = limits.moves_to_go.map_or(DEFAULT_MOVES_TO_GO, |moves_to_go| moves_to_go.max(1) as u128);
+                let max_time = (available_time * MAX_TIME_USAGE_PERCENT / 100).max(1);
+                let soft_limit = (available_time / moves_to_go + limits.increment_time.unwrap_or(0)).min(max_time);
+                let hard_limit = (soft_limit * HARD_LIMIT_FACTOR).min(max_time);
+                TimeManager::from_limits(soft_limit, hard_limit)
+            },
+            None => TimeManager::default(),
+        }
+    }
+
+    #[inline(always)]
+    fn from_limits(soft_limit: u128, hard_limit: u128) -> TimeManager {
+        TimeManager {
+            soft_limit: Some(soft_limit),
+            hard_limit: Some(hard_limit),
+            ..Default::default()
+        }
+    }
+
+    #[inline(always)]
+    pub fn soft_limit(&self) -> Option<u128> {
+        self.soft_limit
+    }
+
+    #[inline(always)]
+    pub fn hard_limit(&self) -> Option<u128> {
+        self.hard_limit
+    }
+
+    #[inline(always)]
+    pub fn is_hard_limit_reached(&self, time_passed: u128) -> bool {
+        self.hard_limit.is_some_and(|hard_limit| time_passed >= hard_limit)
+    }
+
+    #[inline(always)]
+    pub fn should_stop_iterating(&self, time_passed: u128) -> bool {
+        self.soft_limit.is_some_and(|soft_limit| {
+            self.is_only_move || time_passed >= soft_limit * self.soft_limit_percent / 100
+        })
+    }
+
+    #[inline(always)]
+    pub fn set_only_move(&mut self) {
+        self.is_only_move = true;
+    }
+
+    pub fn update(&mut self, best_move: ScoringMove) {
+        let mut soft_limit_percent = 100;
+
+        if let Some(previous_best_move) = self.previous_best_move {
+            if best_move.bit_move != previous_best_move.bit_move {
+                self.stable_iterations = 0;
+                soft_limit_percent = soft_limit_percent * BEST_MOVE_CHANGE_EXTENSION_PERCENT / 100;
+            } else {
+                self.stable_iterations += 1;
+            }
+
+            if Self::is_score_drop(previous_best_move.score, best_move.score) {
+                soft_limit_percent = soft_limit_percent * SCORE_DROP_EXTENSION_PERCENT / 100;
+            }
+        }
+
+        if self.stable_iterations >= STABLE_BEST_MOVE_ITERATIONS {
+            soft_limit_percent = soft_limit_percent * STABLE_BEST_MOVE_REDUCTION_PERCENT / 100;
+        }
+
+        self.soft_limit_percent = soft_limit_percent;
+
+        if best_move.bit_move != BitMove::EMPTY {
+            self.previous_best_move = Some(best_move);
+        }
+    }
+
+    #[inline(always)]
+    fn is_score_drop(previous_score: Score, score: Score) -> bool {
+        !previous_score.is_checkmate() && !score.is_checkmate() && score < previous_score - SCORE_DROP_THRESHOLD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Legal, MoveGeneration, Position, Uci};
+
+    use super::*;
+
+    fn clock_limits(total_time: u128, increment_time: u128, moves_to_go: Option<u32>) -> SearchLimits {
+        SearchLimits {
+            total_time: Some(total_time),
+            increment_time: Some(increment_time),
+            moves_to_go,
+            ..Default::default()
+        }
+    }
+
+    fn scoring_move(position: &Position, move_string: &str, score: i16) -> ScoringMove {
+        let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(position);
+        ScoringMove::new(Uci::parse_move_string(&legal_moves, move_string).unwrap(), Score::from(score))
+    }
+
+    #[test]
+    fn clock_limits_respect_moves_to_go_and_overhead() {
+        let time_manager = TimeManager::new(&clock_limits(10_100, 0, Some(10)), 100);
+        assert_eq!(time_manager.soft_limit(), Some(1000));
+        assert_eq!(time_manager.hard_limit(), Some(4000));
+    }
+
+    #[test]
+    fn hard_limit_never_exceeds_remaining_time() {
+        let time_manager = TimeManager::new(&clock_limits(1000, 5000, Some(1)), 50);
+        assert!(time_manager.hard_limit().unwrap() < 950);
+        assert!(time_manager.soft_limit() <= time_manager.hard_limit());
+    }
+
+    #[test]
+    fn move_time_uses_equal_limits() {
+        let time_manager = TimeManager::new(&SearchLimits::new(None, Some(500)), 20);
+        assert_eq!(time_manager.soft_limit(), Some(480));
+        assert_eq!(time_manager.hard_limit(), Some(480));
+    }
+
+    #[test]
+    fn infinite_search_has_no_limits() {
+        let limits = SearchLimits {
+            infinite: true,
+            ..clock_limits(1000, 0, None)
+        };
+        let time_manager = TimeManager::new(&limits, 0);
+        assert_eq!(time_manager.hard_limit(), None);
+        assert!(!time_manager.should_stop_iterating(u128::MAX / 100));
+    }
+
+    #[test]
+    fn soft_limit_extends_on_best_move_change_and_shortens_when_stable() {
+        let position = Position::starting_position();
+        let mut time_manager = TimeManager::new(&clock_limits(100_000, 0, Some(10)), 0);
+        let time_passed = 11_000;
+
+        time_manager.update(scoring_move(&position, "e2e4", 20));
+        assert!(time_manager.should_stop_iterating(time_passed));
+
+        time_manager.update(scoring_move(&position, "d2d4", 20));
+        assert!(!time_manager.should_stop_iterating(time_passed));
+
+        for _ in 0..STABLE_BEST_MOVE_ITERATIONS {
+            time_manager.update(scoring_move(&position, "d2d4", 20));
+        }
+        assert!(time_manager.should_stop_iterating(7_000));
+    }
+
+    #[test]
+    fn soft_limit_extends_on_score_drop() {
+        let position = Position::starting_position();
+        let mut time_manager = TimeManager::new(&clock_limits(100_000, 0, Some(10)), 0);
+        time_manager.update(scoring_move(&position, "e2e4", 50));
+        time_manager.update(scoring_move(&position, "e2e4", -50));
+        assert!(!time_manager.should_stop_iterating(11_000));
+    }
+}
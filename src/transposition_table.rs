@@ -1,47 +1,50 @@
-#![allow(static_mut_refs)]
+use std::{ops::BitXor, sync::atomic::{AtomicU64, AtomicU8, Ordering}};
 
-use std::ops::BitXor;
+use crate::{BitMove, Score, ScoringMove, ZobristKey};
 
-use crate::{ScoringMove, ZobristKey};
+#[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 const TT_INIT_BYTES_SIZE: usize = 16; // 16MB
 
-static mut TRANSPOSITION_TABLE: Vec<std::sync::Mutex<TTSlot>> = vec![];
+// NOTE: Generations wrap around, since they are stored in the six highest bits of an entry
+const GENERATION_COUNT: u8 = 64;
+#[cfg(feature = "tt_buckets")]
+const AGE_DEPTH_PENALTY: i32 = 8;
 
-pub(crate) struct TranspositionTable;
+#[cfg(feature = "tt_buckets")]
+const BUCKET_SIZE: usize = 4;
 
-#[cfg(feature = "tt_two_tier")]
-struct TTSlot {
-    main_entry: Option<TTEntry>,
-    secondary_entry: Option<TTEntry>,
-}
+#[cfg(all(feature = "tt_two_tier", not(feature = "tt_buckets")))]
+const BUCKET_SIZE: usize = 2;
 
-#[cfg(not(feature = "tt_two_tier"))]
-struct TTSlot {
-    entry: Option<TTEntry>,
-}
+#[cfg(not(any(feature = "tt_two_tier", feature = "tt_buckets")))]
+const BUCKET_SIZE: usize = 1;
 
-#[cfg(feature = "tt_two_tier")]
-impl TTSlot {
-    const EMPTY: TTSlot = TTSlot { main_entry: None, secondary_entry: None };
-}
+const BIT_MOVE_SHIFT: u64 = 0;
+const SCORE_SHIFT: u64 = 32;
+const DEPTH_SHIFT: u64 = 48;
+const NODE_TYPE_SHIFT: u64 = 56;
+const GENERATION_SHIFT: u64 = 58;
 
-#[cfg(not(feature = "tt_two_tier"))]
-impl TTSlot {
-    const EMPTY: TTSlot = TTSlot { entry: None };
+// Lock-free table of buckets, where each entry is stored as its packed data alongside the zobrist
+// key XORed with that data. A torn write from another thread then fails the key verification
+// instead of returning corrupted data: https://www.chessprogramming.org/Shared_Hash_Table#Lock-less
+pub(crate) struct TranspositionTable {
+    buckets: Vec<TTBucket>,
+    generation: AtomicU8,
 }
 
-#[derive(Clone, Copy)]
-pub(crate) struct TTEntry {
-    pub(crate) zobrist_key: ZobristKey,
-    pub(crate) data: TTData,
+#[cfg_attr(feature = "tt_buckets", repr(align(64)))]
+#[derive(Default)]
+struct TTBucket {
+    entries: [TTEntry; BUCKET_SIZE],
 }
 
-impl TTEntry {
-    #[inline(always)]
-    fn new(zobrist_key: ZobristKey, data: TTData) -> TTEntry {
-        TTEntry { zobrist_key, data }
-    }
+#[derive(Default)]
+struct TTEntry {
+    key: AtomicU64,
+    data: AtomicU64,
 }
 
 #[derive(Clone, Copy)]
@@ -59,126 +62,273 @@ pub(crate) enum TTNodeType {
     UpperBound, // α fail aka. Fail-low
 }
 
-impl TranspositionTable {
-    pub(crate) unsafe fn init() {
-        Self::resize(TT_INIT_BYTES_SIZE);
+// Layout: bit move (32) | score (16) | depth (8) | node type (2) | generation (6)
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct PackedTTData(u64);
+
+impl PackedTTData {
+    #[inline(always)]
+    fn pack(data: TTData, generation: u8) -> PackedTTData {
+        PackedTTData(
+            (data.best_move.bit_move.to_bits() as u64) << BIT_MOVE_SHIFT |
+            (i16::from(data.best_move.score) as u16 as u64) << SCORE_SHIFT |
+            (data.depth.min(u8::MAX as u16) as u64) << DEPTH_SHIFT |
+            (data.node_type as u64) << NODE_TYPE_SHIFT |
+            (generation as u64) << GENERATION_SHIFT
+        )
     }
-}
 
-impl TranspositionTable {
     #[inline(always)]
-    pub(crate) fn reset() {
-        unsafe { 
-            TRANSPOSITION_TABLE = (0..TRANSPOSITION_TABLE.len())
-                .map(|_| std::sync::Mutex::new(TTSlot::EMPTY))
-                .collect();
+    fn unpack(self) -> TTData {
+        TTData {
+            best_move: ScoringMove::new(
+                BitMove::from_bits((self.0 >> BIT_MOVE_SHIFT) as u32),
+                Score::from((self.0 >> SCORE_SHIFT) as u16 as i16),
+            ),
+            depth: self.depth() as u16,
+            node_type: match (self.0 >> NODE_TYPE_SHIFT) & 0b11 {
+                0 => TTNodeType::Exact,
+                1 => TTNodeType::LowerBound,
+                _ => TTNodeType::UpperBound,
+            },
         }
     }
 
     #[inline(always)]
-    pub(crate) fn resize(size_mb: usize) {
-        unsafe {
-            TRANSPOSITION_TABLE = (0..size_mb * 1_000_000 / size_of::<TTSlot>())
-                .map(|_| std::sync::Mutex::new(TTSlot::EMPTY))
-                .collect();
-        }
+    fn depth(self) -> u8 {
+        (self.0 >> DEPTH_SHIFT) as u8
     }
 
     #[inline(always)]
-    fn get_slot(zobrist_key: ZobristKey) -> std::sync::MutexGuard<'static, TTSlot> {
-        unsafe {
-            let index = (zobrist_key.0 as usize) % TRANSPOSITION_TABLE.len();
-            TRANSPOSITION_TABLE[index].lock().unwrap()
-        }
+    fn generation(self) -> u8 {
+        (self.0 >> GENERATION_SHIFT) as u8
+    }
+}
+
+impl BitXor<PackedTTData> for ZobristKey {
+    type Output = ZobristKey;
+
+    #[inline(always)]
+    fn bitxor(self, rhs: PackedTTData) -> Self::Output {
+        ZobristKey(self.0 ^ rhs.0)
+    }
+}
+
+impl TTEntry {
+    #[inline(always)]
+    fn load(&self) -> (ZobristKey, PackedTTData) {
+        let data = PackedTTData(self.data.load(Ordering::Relaxed));
+        let key = ZobristKey(self.key.load(Ordering::Relaxed)) ^ data;
+        (key, data)
     }
 
     #[inline(always)]
-    fn verify_key(zobrist_key: ZobristKey, entry: &TTEntry) -> bool {
-        entry.zobrist_key == zobrist_key
+    fn save(&self, zobrist_key: ZobristKey, data: PackedTTData) {
+        self.key.store((zobrist_key ^ data).0, Ordering::Relaxed);
+        self.data.store(data.0, Ordering::Relaxed);
     }
 
     #[inline(always)]
-    fn store_entry(entry: &mut Option<TTEntry>, zobrist_key: ZobristKey, data: TTData) {
-        *entry = Some(TTEntry::new(zobrist_key, data));
+    fn clear(&self) {
+        self.key.store(0, Ordering::Relaxed);
+        self.data.store(0, Ordering::Relaxed);
     }
 }
 
-#[cfg(feature = "tt_two_tier")]
-impl TranspositionTable {
-    // Store using a two-tier approach: https://www.chessprogramming.org/Transposition_Table#Two-tier_System
+impl TTBucket {
     #[inline(always)]
-    pub(crate) fn store(zobrist_key: ZobristKey, data: TTData) {
-        #[allow(unused_mut)]
-        let mut slot = Self::get_slot(zobrist_key);
-        if let Some(existing_entry) = slot.main_entry {
-            if data.depth >= existing_entry.data.depth {
-                Self::store_entry(&mut slot.main_entry, zobrist_key, data);
-            } else {
-                Self::store_entry(&mut slot.secondary_entry, zobrist_key, data);
-            }
-        } else {
-            Self::store_entry(&mut slot.main_entry, zobrist_key, data);
+    fn clear(&self) {
+        for entry in &self.entries {
+            entry.clear();
+        }
+    }
+}
+
+impl Default for TranspositionTable {
+    fn default() -> TranspositionTable {
+        TranspositionTable::new(TT_INIT_BYTES_SIZE)
+    }
+}
+
+impl TranspositionTable {
+    pub(crate) fn new(size_mb: usize) -> TranspositionTable {
+        TranspositionTable {
+            buckets: (0..(size_mb * 1_000_000 / size_of::<TTBucket>()).max(1))
+                .map(|_| TTBucket::default())
+                .collect(),
+            generation: AtomicU8::new(0),
         }
     }
 
     #[inline(always)]
-    pub(crate) fn probe(zobrist_key: ZobristKey) -> Option<TTData> {
-        let slot = Self::get_slot(zobrist_key);
+    pub(crate) fn reset(&self) {
+        #[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
+        self.buckets.par_iter().for_each(TTBucket::clear);
+
+        #[cfg(not(any(feature = "parallel_perft", feature = "lazy_smp")))]
+        self.buckets.iter().for_each(TTBucket::clear);
 
-        if let Some(entry) = slot.main_entry {
-            if Self::verify_key(zobrist_key, &entry) {
-                return Some(entry.data);
+        self.generation.store(0, Ordering::Relaxed);
+    }
+
+    // NOTE: Should be called once before every search, so entries from previous searches age
+    #[inline(always)]
+    pub(crate) fn new_search(&self) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.generation.store((generation + 1) % GENERATION_COUNT, Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    fn get_bucket(&self, zobrist_key: ZobristKey) -> &TTBucket {
+        &self.buckets[(zobrist_key.0 as usize) % self.buckets.len()]
+    }
+
+    #[cfg(feature = "tt_buckets")]
+    #[inline(always)]
+    fn age(&self, data: PackedTTData) -> u8 {
+        (GENERATION_COUNT + self.generation.load(Ordering::Relaxed) - data.generation()) % GENERATION_COUNT
+    }
+
+    // NOTE: Shallow entries from old searches are the first to be replaced
+    #[cfg(feature = "tt_buckets")]
+    #[inline(always)]
+    fn replacement_priority(&self, data: PackedTTData) -> i32 {
+        data.depth() as i32 - AGE_DEPTH_PENALTY * self.age(data) as i32
+    }
+
+    #[cfg(feature = "tt_buckets")]
+    #[inline(always)]
+    pub(crate) fn store(&self, zobrist_key: ZobristKey, data: TTData) {
+        let bucket = self.get_bucket(zobrist_key);
+        let packed_data = PackedTTData::pack(data, self.generation.load(Ordering::Relaxed));
+
+        let mut replaced_entry = &bucket.entries[0];
+        let mut lowest_priority = i32::MAX;
+        for entry in &bucket.entries {
+            let (entry_key, entry_data) = entry.load();
+            if entry_key == zobrist_key {
+                replaced_entry = entry;
+                break;
             }
-        }
 
-        if let Some(entry) = slot.secondary_entry {
-            if Self::verify_key(zobrist_key, &entry) {
-                return Some(entry.data);
+            let priority = self.replacement_priority(entry_data);
+            if priority < lowest_priority {
+                lowest_priority = priority;
+                replaced_entry = entry;
             }
         }
 
-        None
+        replaced_entry.save(zobrist_key, packed_data);
     }
-}
 
-#[cfg(not(feature = "tt_two_tier"))]
-impl TranspositionTable {
     // Store using a two-tier approach: https://www.chessprogramming.org/Transposition_Table#Two-tier_System
+    #[cfg(all(feature = "tt_two_tier", not(feature = "tt_buckets")))]
     #[inline(always)]
-    pub(crate) fn store(zobrist_key: ZobristKey, data: TTData) {
-        let mut slot = Self::get_slot(zobrist_key);
-        Self::store_entry(&mut slot.entry, zobrist_key, data);
+    pub(crate) fn store(&self, zobrist_key: ZobristKey, data: TTData) {
+        let bucket = self.get_bucket(zobrist_key);
+        let packed_data = PackedTTData::pack(data, self.generation.load(Ordering::Relaxed));
+        let (_, main_data) = bucket.entries[0].load();
+
+        if data.depth >= main_data.depth() as u16 {
+            bucket.entries[0].save(zobrist_key, packed_data);
+        } else {
+            bucket.entries[1].save(zobrist_key, packed_data);
+        }
     }
 
+    #[cfg(not(any(feature = "tt_two_tier", feature = "tt_buckets")))]
     #[inline(always)]
-    pub(crate) fn probe(zobrist_key: ZobristKey) -> Option<TTData> {
-        let slot = Self::get_slot(zobrist_key);
+    pub(crate) fn store(&self, zobrist_key: ZobristKey, data: TTData) {
+        let packed_data = PackedTTData::pack(data, self.generation.load(Ordering::Relaxed));
+        self.get_bucket(zobrist_key).entries[0].save(zobrist_key, packed_data);
+    }
 
-        if let Some(entry) = slot.entry {
-            if Self::verify_key(zobrist_key, &entry) {
-                return Some(entry.data);
+    #[inline(always)]
+    pub(crate) fn probe(&self, zobrist_key: ZobristKey) -> Option<TTData> {
+        self.get_bucket(zobrist_key)
+            .entries
+            .iter()
+            .map(TTEntry::load)
+            .find(|&(entry_key, _)| entry_key == zobrist_key)
+            .map(|(_, entry_data)| entry_data.unpack())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Legal, MoveGeneration, Position};
+
+    use super::*;
+
+    fn tt_data(bit_move: BitMove, score: i16, depth: u16, node_type: TTNodeType) -> TTData {
+        TTData {
+            best_move: ScoringMove::new(bit_move, Score::from(score)),
+            depth,
+            node_type,
+        }
+    }
+
+    #[test]
+    fn packed_data_round_trips() {
+        let position = Position::starting_position();
+        for bit_move in MoveGeneration::generate_moves::<BitMove, Legal>(&position) {
+            for (score, node_type) in [(-9990, TTNodeType::UpperBound), (0, TTNodeType::Exact), (123, TTNodeType::LowerBound)] {
+                let data = PackedTTData::pack(tt_data(bit_move, score, 12, node_type), 63).unpack();
+                assert_eq!(data.best_move, ScoringMove::new(bit_move, Score::from(score)));
+                assert_eq!(data.depth, 12);
+                assert_eq!(data.node_type, node_type);
             }
         }
+    }
+
+    #[test]
+    fn probe_verifies_key() {
+        let transposition_table = TranspositionTable::new(1);
+        let zobrist_key = Position::starting_position().zobrist_key;
+        let data = tt_data(BitMove::EMPTY, 42, 3, TTNodeType::Exact);
+        transposition_table.store(zobrist_key, data);
+        assert_eq!(transposition_table.probe(zobrist_key).map(|data| data.best_move.score), Some(Score::from(42)));
 
-        None
+        let colliding_key = ZobristKey(zobrist_key.0 + transposition_table.buckets.len() as u64);
+        assert!(transposition_table.probe(colliding_key).is_none());
+
+        transposition_table.reset();
+        assert!(transposition_table.probe(zobrist_key).is_none());
     }
-}
 
-impl BitXor<TTData> for ZobristKey {
-    type Output = ZobristKey;
+    #[test]
+    #[cfg(feature = "tt_buckets")]
+    fn store_replaces_shallow_entries_first() {
+        let transposition_table = TranspositionTable::new(1);
+        let bucket_count = transposition_table.buckets.len() as u64;
+        let keys: Vec<ZobristKey> = (1..=BUCKET_SIZE as u64 + 1).map(|index| ZobristKey(index * bucket_count)).collect();
 
-    #[inline(always)]
-    #[cfg(feature = "bb_array")]
-    fn bitxor(self, rhs: TTData) -> Self::Output {
-        unsafe { std::mem::transmute::<u64, ZobristKey>(self.0 ^ std::mem::transmute::<TTData, u64>(rhs)) }
+        for (index, &zobrist_key) in keys[..BUCKET_SIZE].iter().enumerate() {
+            transposition_table.store(zobrist_key, tt_data(BitMove::EMPTY, 0, 10 + index as u16, TTNodeType::Exact));
+        }
+
+        transposition_table.store(keys[BUCKET_SIZE], tt_data(BitMove::EMPTY, 0, 1, TTNodeType::Exact));
+        assert!(transposition_table.probe(keys[0]).is_none());
+        assert!(keys[1..].iter().all(|&zobrist_key| transposition_table.probe(zobrist_key).is_some()));
     }
 
-    #[inline(always)]
-    #[cfg(feature = "bb")]
-    // NOTE: This should NEVER happen. The function is defined only because of compile time errors
-    // that arise when a bit move is not 16 bits in size, which results in TTData being more than
-    // 64 bits in size.
-    fn bitxor(self, rhs: TTData) -> Self::Output {
-        unsafe { std::mem::zeroed() }
+    #[test]
+    #[cfg(feature = "tt_buckets")]
+    fn store_replaces_old_entries_before_new_ones() {
+        let transposition_table = TranspositionTable::new(1);
+        let bucket_count = transposition_table.buckets.len() as u64;
+        let keys: Vec<ZobristKey> = (1..=BUCKET_SIZE as u64 + 1).map(|index| ZobristKey(index * bucket_count)).collect();
+
+        for &zobrist_key in &keys[..BUCKET_SIZE - 1] {
+            transposition_table.store(zobrist_key, tt_data(BitMove::EMPTY, 0, 10, TTNodeType::Exact));
+        }
+
+        transposition_table.new_search();
+        transposition_table.new_search();
+        transposition_table.store(keys[BUCKET_SIZE - 1], tt_data(BitMove::EMPTY, 0, 5, TTNodeType::Exact));
+        transposition_table.store(keys[BUCKET_SIZE], tt_data(BitMove::EMPTY, 0, 1, TTNodeType::Exact));
+        assert!(transposition_table.probe(keys[0]).is_none());
+        assert!(transposition_table.probe(keys[BUCKET_SIZE - 1]).is_some());
+        assert!(transposition_table.probe(keys[BUCKET_SIZE]).is_some());
     }
 }
@@ -1,11 +1,24 @@
 use std::{io::{self, BufRead}, process::exit, sync::{atomic::Ordering, mpsc}, thread};
 
-use crate::{BitMove, Color, EvalPosition, FenString, HistoryHeuristic, KillerMoves, Legal, MoveFlag, MoveGeneration, MoveList, MoveStringParseError, Perft, Position, Search, Square, TranspositionTable, UciParseError};
+use crate::skill::{MAX_ELO, MAX_SKILL_LEVEL, MIN_ELO, MIN_SKILL_LEVEL};
+use crate::time_manager::DEFAULT_MOVE_OVERHEAD_MS;
+use crate::{BitMove, Color, EvalPosition, FenString, Legal, MoveFlag, MoveGeneration, MoveList, MoveStringParseError, Perft, Position, Search, SearchLimits, Skill, Square, UciParseError};
 
 const DEFAULT_TT_SIZE_MB: usize = 16;
 const MIN_TT_SIZE_MB: usize = 1;
 const MAX_TT_SIZE_MB: usize = 10_000;
 
+const DEFAULT_MULTI_PV: usize = 1;
+const MIN_MULTI_PV: usize = 1;
+const MAX_MULTI_PV: usize = 255;
+
+const GO_KEYWORDS: [&str; 12] = ["searchmoves", "ponder", "wtime", "btime", "winc", "binc", "movestogo", "depth", "nodes", "mate", "movetime", "infinite"];
+
+const MIN_MOVE_OVERHEAD_MS: u128 = 0;
+const MAX_MOVE_OVERHEAD_MS: u128 = 5000;
+
+const DEFAULT_ELO: u16 = 1500;
+
 const DEFAULT_NUM_THREADS: usize = 1;
 const MIN_NUM_THREADS: usize = 0;
 const MAX_NUM_THREADS: usize = 1024;
@@ -13,6 +26,9 @@ const MAX_NUM_THREADS: usize = 1024;
 pub struct Uci {
     position: Position,
     search: Search,
+    skill_level: u8,
+    limit_strength: bool,
+    elo: u16,
 }
 
 impl Default for Uci {
@@ -23,6 +39,9 @@ impl Default for Uci {
         Self {
             position: Position::starting_position(),
             search,
+            skill_level: MAX_SKILL_LEVEL,
+            limit_strength: false,
+            elo: DEFAULT_ELO,
         }
     }
 }
@@ -35,12 +54,21 @@ impl Uci {
         let (uci_command_tx, uci_command_rx) = mpsc::channel();
 
         let stop_calculating = self.search.get_stop_calculating();
+        let ponder_hit = self.search.get_ponder_hit();
         
         thread::spawn(move || {
             let mut lines = io::stdin().lock().lines();
             while let Some(Ok(line)) = lines.next() {
                 match line.as_str() {
                     "stop" | "s" => stop_calculating.store(true, Ordering::Relaxed),
+                    "ponderhit" => ponder_hit.store(true, Ordering::Relaxed),
+                    // NOTE: An infinite search only ends when stopped, so quitting has to stop it first
+                    "quit" | "exit" | "q" | "e" => {
+                        stop_calculating.store(true, Ordering::Relaxed);
+                        if uci_command_tx.send(line).is_err() {
+                            break;
+                        }
+                    },
                     _ => if uci_command_tx.send(line).is_err() {
                         break;
                     },
@@ -73,6 +101,15 @@ impl Uci {
         println!("option name Threads type spin default {DEFAULT_NUM_THREADS} min {MIN_NUM_THREADS} max {MAX_NUM_THREADS}");
         println!("option name Hash type spin default {DEFAULT_TT_SIZE_MB} min {MIN_TT_SIZE_MB} max {MAX_TT_SIZE_MB}");
         println!("option name Clear Hash type button");
+        println!("option name Move Overhead type spin default {DEFAULT_MOVE_OVERHEAD_MS} min {MIN_MOVE_OVERHEAD_MS} max {MAX_MOVE_OVERHEAD_MS}");
+        println!("option name Ponder type check default false");
+        println!("option name MultiPV type spin default {DEFAULT_MULTI_PV} min {MIN_MULTI_PV} max {MAX_MULTI_PV}");
+        println!("option name Skill Level type spin default {MAX_SKILL_LEVEL} min {MIN_SKILL_LEVEL} max {MAX_SKILL_LEVEL}");
+        println!("option name UCI_LimitStrength type check default false");
+        println!("option name UCI_Elo type spin default {DEFAULT_ELO} min {MIN_ELO} max {MAX_ELO}");
+        println!("option name OwnBook type check default true");
+        println!("option name BookFile type string default <empty>");
+        println!("option name BestBookMove type check default false");
         println!("option name SyzygyPath type string default tables/syzygy");
         println!("uciok");
     }
@@ -105,15 +142,15 @@ impl Uci {
                         Ok(())
                     },
                     "bench" | "benchmedium" => {
-                        Perft::medium_perft_tests();
+                        self.run_perft(Perft::medium_perft_tests);
                         Ok(())
                     }
                     "benchlong" => {
-                        Perft::long_perft_tests();
+                        self.run_perft(Perft::long_perft_tests);
                         Ok(())
                     },
                     "benchshort" => {
-                        Perft::short_perft_tests();
+                        self.run_perft(Perft::short_perft_tests);
                         Ok(())
                     },
                     "setoption" => {
@@ -128,16 +165,14 @@ impl Uci {
     }
 
     fn ucinewgame(&mut self) {
-        KillerMoves::reset();
-        HistoryHeuristic::reset();
-        TranspositionTable::reset();
+        self.search.clear_tables();
         self.search.in_opening = true;
         self.position = Position::starting_position();
     }
 
     fn parse_setoption(&mut self, line: &str, words: &[&str]) -> Result<(), UciParseError> {
         if line == "setoption name Clear Hash" {
-            TranspositionTable::reset();
+            self.search.clear_transposition_table();
             println!("info string transposition table reset successfully");
             Ok(())
         } else if line.starts_with("setoption name Threads value") {
@@ -148,7 +183,7 @@ impl Uci {
                     return Err(UciParseError::ParamRange("Threads"));
                 }
                 
-                crate::GlobalThreadPool::set_threadpool(num_threads);
+                self.search.set_num_threads(num_threads);
                 println!("info string set threads to {num_threads} successfully");
                 Ok(())
             }
@@ -168,13 +203,98 @@ impl Uci {
             #[cfg(not(feature = "syzygy_tablebase"))]
             Err(UciParseError::DisabledFeatureError("Syzygy Tablebase"))
 
+        } else if line.starts_with("setoption name OwnBook value") {
+            #[cfg(feature = "opening_book")]
+            {
+                let own_book = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("OwnBook"))?;
+                self.search.set_own_book(own_book);
+                println!("info string set own book to {own_book} successfully");
+                Ok(())
+            }
+
+            #[cfg(not(feature = "opening_book"))]
+            Err(UciParseError::DisabledFeatureError("Opening Book"))
+
+        } else if line.starts_with("setoption name BookFile value") {
+            #[cfg(feature = "opening_book")]
+            {
+                // NOTE: The path may contain spaces and is empty to use the Lichess backend
+                let path = line.trim_start_matches("setoption name BookFile value").trim();
+                self.search.set_book_file(if path == "<empty>" { "" } else { path });
+                Ok(())
+            }
+
+            #[cfg(not(feature = "opening_book"))]
+            Err(UciParseError::DisabledFeatureError("Opening Book"))
+
+        } else if line.starts_with("setoption name BestBookMove value") {
+            #[cfg(feature = "opening_book")]
+            {
+                let best_book_move = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("BestBookMove"))?;
+                self.search.set_best_book_move(best_book_move);
+                println!("info string set best book move to {best_book_move} successfully");
+                Ok(())
+            }
+
+            #[cfg(not(feature = "opening_book"))]
+            Err(UciParseError::DisabledFeatureError("Opening Book"))
+
+        } else if line.starts_with("setoption name Move Overhead value") {
+            let move_overhead = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("Move Overhead"))?;
+            if !(MIN_MOVE_OVERHEAD_MS..=MAX_MOVE_OVERHEAD_MS).contains(&move_overhead) {
+                return Err(UciParseError::ParamRange("Move Overhead"));
+            }
+
+            self.search.set_move_overhead(move_overhead);
+            println!("info string set move overhead to {move_overhead}ms successfully");
+            Ok(())
+        } else if line.starts_with("setoption name Ponder value") {
+            let ponder = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("Ponder"))?;
+            self.search.set_ponder(ponder);
+            println!("info string set ponder to {ponder} successfully");
+            Ok(())
+        } else if line.starts_with("setoption name MultiPV value") {
+            let multi_pv = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("MultiPV"))?;
+            if !(MIN_MULTI_PV..=MAX_MULTI_PV).contains(&multi_pv) {
+                return Err(UciParseError::ParamRange("MultiPV"));
+            }
+
+            self.search.set_multi_pv(multi_pv);
+            println!("info string set multipv to {multi_pv} successfully");
+            Ok(())
+        } else if line.starts_with("setoption name Skill Level value") {
+            let skill_level = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("Skill Level"))?;
+            if !(MIN_SKILL_LEVEL..=MAX_SKILL_LEVEL).contains(&skill_level) {
+                return Err(UciParseError::ParamRange("Skill Level"));
+            }
+
+            self.skill_level = skill_level;
+            self.update_skill();
+            println!("info string set skill level to {skill_level} successfully");
+            Ok(())
+        } else if line.starts_with("setoption name UCI_LimitStrength value") {
+            let limit_strength = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("UCI_LimitStrength"))?;
+            self.limit_strength = limit_strength;
+            self.update_skill();
+            println!("info string set limit strength to {limit_strength} successfully");
+            Ok(())
+        } else if line.starts_with("setoption name UCI_Elo value") {
+            let elo = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("UCI_Elo"))?;
+            if !(MIN_ELO..=MAX_ELO).contains(&elo) {
+                return Err(UciParseError::ParamRange("UCI_Elo"));
+            }
+
+            self.elo = elo;
+            self.update_skill();
+            println!("info string set elo to {elo} successfully");
+            Ok(())
         } else if line.starts_with("setoption name Hash value") {
             let tt_size_mb = words.last().unwrap().parse().map_err(|_| UciParseError::ParamValue("Transposition Table Size (MB)"))?;
-            if tt_size_mb < MIN_TT_SIZE_MB || tt_size_mb > MAX_TT_SIZE_MB {
+            if !(MIN_TT_SIZE_MB..=MAX_TT_SIZE_MB).contains(&tt_size_mb) {
                 return Err(UciParseError::ParamRange("Transposition Table Size (MB)"));
             }
             
-            TranspositionTable::resize(tt_size_mb);
+            self.search.resize_transposition_table(tt_size_mb);
             println!("info string set transposition table size to {tt_size_mb}MB successfully");
             Ok(())
         } else {
@@ -182,6 +302,14 @@ impl Uci {
         }
     }
 
+    // NOTE: The elo only takes effect while strength is limited, in which case it overrides the skill level
+    fn update_skill(&mut self) {
+        match self.limit_strength {
+            true => self.search.set_skill_level(Skill::level_from_elo(self.elo)),
+            false => self.search.set_skill_level(self.skill_level),
+        }
+    }
+
     fn parse_position(&mut self, line: &str) -> Result<(), UciParseError> {
         let fen_index_option = line.find("fen");
         let startpos_index_option = line.find("startpos");
@@ -223,11 +351,12 @@ impl Uci {
             for move_string in &move_strings {
                 let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&self.position);
                 let bit_move = Self::parse_move_string(&legal_moves, move_string)?;
+                self.search.zobrist_key_history.push(self.position.zobrist_key);
                 self.position.make_move(bit_move);
-                if bit_move.is_pp_capture_or_castle(&self.position) {
-                    self.search.zobrist_key_history = Vec::new();
-                } else {
-                    self.search.zobrist_key_history.push(self.position.zobrist_key);
+
+                // NOTE: Positions from before an irreversible move can't occur again
+                if self.position.halfmove_clock == 0 {
+                    self.search.zobrist_key_history.clear();
                 }
             }
         }
@@ -257,21 +386,57 @@ impl Uci {
             Color::White => "winc",
             Color::Black => "binc",
         }, UciParseError::ParamValue("winc/binc"))?;
+        let moves_to_go: Option<u32> = Self::parse_parameter_value(words, "movestogo", UciParseError::ParamValue("movestogo"))?;
+        let nodes: Option<u64> = Self::parse_parameter_value(words, "nodes", UciParseError::ParamValue("nodes"))?;
+        let mate: Option<usize> = Self::parse_parameter_value(words, "mate", UciParseError::ParamValue("mate"))?;
+        let infinite = words.contains(&"infinite");
+        let ponder = words.contains(&"ponder");
+        let search_moves = self.parse_search_moves(words)?;
 
         if let Some(perft_depth) = perft_depth {
-            Perft::perft_test(&self.position, perft_depth, true);
+            self.run_perft(|| { Perft::perft_test(&self.position, perft_depth, true); });
             return Ok(());
         }
 
-        let stop_time = if move_time.is_some() {
-            move_time
-        } else {
-            Search::calculate_stop_time(total_time, increment_time)
+        let limits = SearchLimits {
+            depth,
+            stop_time: move_time,
+            total_time,
+            increment_time,
+            moves_to_go,
+            nodes,
+            mate,
+            search_moves,
+            infinite,
+            ponder,
         };
 
-        self.search.go(&self.position, depth, stop_time);
+        self.search.go_with_limits(&self.position, limits);
         Ok(())
     }
+
+    // NOTE: Perft is run inside the search thread pool, so it respects the Threads option
+    fn run_perft(&self, perft: impl FnOnce() + Send) {
+        #[cfg(any(feature = "parallel_perft", feature = "lazy_smp"))]
+        self.search.context.thread_pool.install(perft);
+
+        #[cfg(not(any(feature = "parallel_perft", feature = "lazy_smp")))]
+        perft();
+    }
+
+    fn parse_search_moves(&self, words: &[&str]) -> Result<Vec<BitMove>, UciParseError> {
+        match words.iter().position(|&word| word == "searchmoves") {
+            Some(word_index) => {
+                let legal_moves = MoveGeneration::generate_moves::<BitMove, Legal>(&self.position);
+                words[word_index + 1..]
+                    .iter()
+                    .take_while(|&&word| !GO_KEYWORDS.contains(&word))
+                    .map(|move_string| Self::parse_move_string(&legal_moves, move_string).map_err(UciParseError::from))
+                    .collect()
+            },
+            None => Ok(Vec::new()),
+        }
+    }
     
     #[inline(always)]
     pub(crate) fn parse_move_string(move_list: &MoveList<BitMove>, move_string: &str) -> Result<BitMove, MoveStringParseError> {